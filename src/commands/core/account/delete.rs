@@ -7,7 +7,7 @@ use log::*;
 
 use crate::{
     exit_codes::loggedin_only,
-    functions::{doasisay, get_url, post, v1_handle},
+    functions::{doasisay, get_url, post, read_pw, v1_handle},
     CREDS,
 };
 
@@ -16,9 +16,9 @@ use crate::{
 #[argp(subcommand, name = "delete")]
 /// Delete an existing GM account.
 pub struct Delete {
-    #[argp(option, default = "crate::functions::read_pw()", short = 'p')]
+    #[argp(option, short = 'p')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub password: String,
+    pub password: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -34,7 +34,7 @@ impl CommandTrait for Delete {
 
         let body = V1TokenPassword {
             token: creds.token.clone(),
-            password: self.password.clone(),
+            password: self.password.clone().unwrap_or_else(read_pw),
         };
 
         let url = get_url("/api/accounts/v1/delete").await;
@@ -7,7 +7,7 @@ use log::*;
 
 use crate::{
     exit_codes::loggedin_only,
-    functions::{doasisay, get_url, post, v1_handle},
+    functions::{doasisay, get_url, post, read_pw, v1_handle},
     CREDS,
 };
 
@@ -19,9 +19,9 @@ pub struct Email {
     #[argp(positional)]
     /// Your new linked email address.
     pub new: String,
-    #[argp(option, default = "crate::functions::read_pw()", short = 'p')]
+    #[argp(option, short = 'p')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub password: String,
+    pub password: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -37,7 +37,7 @@ impl CommandTrait for Email {
         trace!("Logged in, proceeding with regenerating token.");
         let body = V1ChangeEmail {
             token: creds.token.clone(),
-            password: self.password.clone(),
+            password: self.password.clone().unwrap_or_else(read_pw),
             new: self.new.clone(),
         };
 
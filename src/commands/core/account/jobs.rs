@@ -1,13 +1,18 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+    time::Duration,
+};
 
 use argp::FromArgs;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::{V1Response, V1TokenOnly};
+use command_macro_derive::Command;
+use goodmorning_bindings::services::v1::{V1Response, V1TokenOnly, V1Unqueue};
 use log::*;
 
 use crate::{
     exit_codes::loggedin_only,
-    functions::{get_url, post, v1_handle},
+    functions::{get_url, jobs, jobs_to_string, post, v1_handle, wait_job, POLL_INTERVAL},
     CREDS,
 };
 
@@ -15,22 +20,96 @@ use crate::{
 #[derive(FromArgs)]
 #[argp(subcommand, name = "jobs")]
 /// List running and queued jobs.
-pub struct Jobs {}
+pub struct Jobs {
+    #[argp(switch, short = 'w')]
+    /// Keep refreshing until there are no jobs left.
+    pub watch: bool,
+    #[argp(subcommand)]
+    pub subcommand: Option<JobsSubcommands>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum JobsSubcommands {
+    Cancel(JobsCancel),
+    Wait(JobsWait),
+}
 
 #[async_trait::async_trait]
 impl CommandTrait for Jobs {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(subcommand) = &self.subcommand {
+            return subcommand.run().await;
+        }
+
         let creds = unsafe { CREDS.get_mut().unwrap() };
         if !creds.is_loggedin() {
             loggedin_only()
         }
 
         trace!("Logged in, proceeding with listing jobs.");
-        let body = V1TokenOnly {
+        if !self.watch {
+            let body = V1TokenOnly {
+                token: creds.token.clone(),
+            };
+
+            let url = get_url("/api/jobs/v1/jobs").await;
+
+            let res: V1Response = post(&url, body).await?;
+            v1_handle(&res)?;
+
+            return Ok(());
+        }
+
+        let terminal = io::stdout().is_terminal();
+        loop {
+            let (current, queue) = jobs().await?;
+            let done = current.is_empty() && queue.is_empty();
+
+            if terminal {
+                // clear screen and move to top left before redrawing
+                print!("\x1b[2J\x1b[H");
+            }
+            println!("{}", jobs_to_string("current", current));
+            println!("{}", jobs_to_string("queue", queue));
+
+            if done {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_secs(POLL_INTERVAL)).await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "cancel")]
+/// Remove a job from the queue.
+pub struct JobsCancel {
+    #[argp(positional)]
+    /// ID of the job.
+    pub id: u64,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for JobsCancel {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with unqueuing job.");
+        let body = V1Unqueue {
             token: creds.token.clone(),
+            id: self.id,
         };
 
-        let url = get_url("/api/jobs/v1/jobs").await;
+        let url = get_url("/api/jobs/v1/unqueue").await;
 
         let res: V1Response = post(&url, body).await?;
         v1_handle(&res)?;
@@ -38,3 +117,29 @@ impl CommandTrait for Jobs {
         Ok(())
     }
 }
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "wait")]
+/// Wait until a job is no longer running or queued.
+pub struct JobsWait {
+    #[argp(positional)]
+    /// ID of the job.
+    pub id: u64,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for JobsWait {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with waiting for job.");
+        wait_job(self.id).await?;
+        println!("Job [{}] is no longer running or queued.", self.id);
+
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
-use std::error::Error;
+use std::{env, error::Error, io::Read};
 
 use argp::FromArgs;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::{V1IdentifierType, V1PasswordId, V1Response};
+use config_macro::ConfigTrait;
+use goodmorning_bindings::services::v1::{V1IdentifierType, V1PasswordId, V1Response, V1TokenOnly};
 use log::*;
 
 use crate::{
-    exit_codes::loggedin_not_allowed,
-    functions::{get_url, post, v1_handle},
+    exit_codes::{loggedin_not_allowed, missing_argument, unexpected_response},
+    functions::{get_url, instance_url, post, prompt, read_pw, v1_handle},
+    structs::CredsConfig,
     CREDS, INSTANCE,
 };
 
@@ -16,23 +18,19 @@ use crate::{
 #[argp(subcommand, name = "login")]
 /// Login to an existing GM account.
 pub struct Login {
-    #[argp(
-        option,
-        default = "crate::functions::prompt_sync(\"Identifier\")",
-        short = 'u'
-    )]
+    #[argp(option, short = 'u')]
     /// Username or email address.
-    pub identifier: String,
-    #[argp(
-        option,
-        default = "crate::functions::prompt_sync(\"Instance\")",
-        short = 'i'
-    )]
-    /// Instance domain or IP.
-    pub instance: String,
-    #[argp(option, default = "crate::functions::read_pw()", short = 'p')]
+    pub identifier: Option<String>,
+    #[argp(option, short = 'i')]
+    /// Instance url, the scheme defaults to https if left out.
+    pub instance: Option<String>,
+    #[argp(option, short = 'p')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub password: String,
+    pub password: Option<String>,
+    #[argp(switch)]
+    /// Read an API token from stdin instead of logging in with a password,
+    /// the token can also be passed in `BREWER_TOKEN`.
+    pub token_stdin: bool,
 }
 
 #[async_trait::async_trait]
@@ -43,8 +41,21 @@ impl CommandTrait for Login {
         }
 
         trace!("Not logged in, proceeding with login.");
-        unsafe { INSTANCE.set(self.instance.clone()).unwrap() };
-        let r#type = if self.identifier.contains('@') {
+        let token = self.token()?;
+        if let Some(token) = token {
+            return self.token_login(token).await;
+        }
+
+        let identifier = match &self.identifier {
+            Some(identifier) => identifier.clone(),
+            None => prompt("Identifier").await,
+        };
+        let instance = match &self.instance {
+            Some(instance) => instance.clone(),
+            None => prompt("Instance").await,
+        };
+        unsafe { INSTANCE.set(instance_url(&instance)).unwrap() };
+        let r#type = if identifier.contains('@') {
             debug!("Identifier is an email address");
             V1IdentifierType::Email
         } else {
@@ -53,9 +64,9 @@ impl CommandTrait for Login {
         };
 
         let body = V1PasswordId {
-            identifier: self.identifier.clone(),
+            identifier,
             identifier_type: r#type,
-            password: self.password.clone(),
+            password: self.password.clone().unwrap_or_else(read_pw),
         };
 
         let url = get_url("/api/accounts/v1/login").await;
@@ -66,3 +77,68 @@ impl CommandTrait for Login {
         Ok(())
     }
 }
+
+impl Login {
+    /// Token from stdin if `--token-stdin` is set, otherwise from `BREWER_TOKEN`.
+    fn token(&self) -> Result<Option<String>, Box<dyn Error>> {
+        let token = if self.token_stdin {
+            trace!("Reading token from stdin.");
+            let mut token = String::new();
+            std::io::stdin().read_to_string(&mut token)?;
+            token
+        } else {
+            match env::var("BREWER_TOKEN") {
+                Ok(token) => {
+                    trace!("Using token from BREWER_TOKEN.");
+                    token
+                }
+                Err(_) => return Ok(None),
+            }
+        };
+
+        let token = token.trim();
+        if token.is_empty() {
+            missing_argument("token")
+        }
+
+        Ok(Some(token.to_string()))
+    }
+
+    /// Checks the token against the server and stores it with the account id it belongs to.
+    async fn token_login(&self, token: String) -> Result<(), Box<dyn Error>> {
+        let instance = match &self.instance {
+            Some(instance) => instance.clone(),
+            None => prompt("Instance").await,
+        };
+        let instance = instance_url(&instance);
+        unsafe { INSTANCE.set(instance.clone()).unwrap() };
+
+        let body = V1TokenOnly {
+            token: token.clone(),
+        };
+
+        let url = get_url("/api/accounts/v1/profile").await;
+
+        let res: V1Response = post(&url, body).await?;
+        let id = match res {
+            V1Response::Profile { account, .. } => account.id,
+            res => {
+                v1_handle(&res)?;
+                unexpected_response("Profile", res);
+                unreachable!()
+            }
+        };
+
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        *creds = CredsConfig {
+            id,
+            instance,
+            token,
+        };
+        trace!("Writing account creds to {:?}", CredsConfig::path());
+        creds.save()?;
+        println!("You are now logged in");
+
+        Ok(())
+    }
+}
@@ -4,6 +4,12 @@ mod login;
 pub use login::*;
 mod logout;
 pub use logout::*;
+mod whoami;
+pub use whoami::*;
+mod profile;
+pub use profile::*;
+mod trigger;
+pub use trigger::*;
 mod regen;
 pub use regen::*;
 mod delete;
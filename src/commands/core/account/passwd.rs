@@ -7,7 +7,7 @@ use log::*;
 
 use crate::{
     exit_codes::loggedin_only,
-    functions::{get_url, post, v1_handle},
+    functions::{get_url, post, read_pw_confirm, read_pw_old, v1_handle},
     CREDS,
 };
 
@@ -16,12 +16,12 @@ use crate::{
 #[argp(subcommand, name = "passwd")]
 /// Change account password.
 pub struct Passwd {
-    #[argp(option, default = "crate::functions::read_pw_old()", short = 'p')]
+    #[argp(option, short = 'p')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub old: String,
-    #[argp(option, default = "crate::functions::read_pw_confirm()", short = 'n')]
+    pub old: Option<String>,
+    #[argp(option, short = 'n')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub new: String,
+    pub new: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -35,8 +35,8 @@ impl CommandTrait for Passwd {
         trace!("Logged in, proceeding with regenerating token.");
         let body = V1ChangePassword {
             token: creds.token.clone(),
-            old: self.old.clone(),
-            new: self.new.clone(),
+            old: self.old.clone().unwrap_or_else(read_pw_old),
+            new: self.new.clone().unwrap_or_else(|| read_pw_confirm("new")),
         };
 
         let url = get_url("/api/accounts/v1/change-password").await;
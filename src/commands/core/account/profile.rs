@@ -0,0 +1,280 @@
+use std::{error::Error, mem, path::PathBuf};
+
+use argp::FromArgs;
+use chrono::{Datelike, NaiveDate};
+use command_macro::CommandTrait;
+use command_macro_derive::Command;
+use goodmorning_bindings::{
+    services::v1::{V1Response, V1SetProfile, V1TokenOnly},
+    structs::{BirthDay, CakeDay, ProfileCustomisable, ProfileDetail},
+};
+use log::*;
+
+use crate::{
+    exit_codes::{invalid_argument, loggedin_only, missing_argument, unexpected_response},
+    functions::{get, get_url, get_url_instance, post, upload, v1_handle},
+    CREDS,
+};
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "profile")]
+/// View and edit account profiles.
+pub struct Profile {
+    #[argp(subcommand)]
+    pub subcommand: ProfileSubcommands,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum ProfileSubcommands {
+    Show(ProfileShow),
+    Set(ProfileSet),
+    ResetPfp(ProfileResetPfp),
+    SetPfp(ProfileSetPfp),
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for Profile {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        self.subcommand.run().await
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "show")]
+/// Show the profile of a user.
+pub struct ProfileShow {
+    #[argp(positional)]
+    /// User ID or username, defaults to yourself.
+    pub user: Option<String>,
+    #[argp(option, short = 'i')]
+    /// Instance of the user, defaults to the logged in instance.
+    pub instance: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for ProfileShow {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        let path = match &self.user {
+            Some(user) if user.parse::<i64>().is_ok() => {
+                format!("/api/accounts/v1/profile/id/{user}")
+            }
+            Some(user) => format!("/api/accounts/v1/profile/name/{user}"),
+            None if creds.is_loggedin() => format!("/api/accounts/v1/profile/id/{}", creds.id),
+            None => {
+                missing_argument("user");
+                unreachable!()
+            }
+        };
+
+        let url = match &self.instance {
+            Some(instance) => get_url_instance(&path, instance),
+            None => get_url(&path).await,
+        };
+
+        trace!("Proceeding with showing profile.");
+        let res: V1Response = get(&url).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "set")]
+/// Change your profile, anything left out stays as it is.
+pub struct ProfileSet {
+    #[argp(option, short = 'd')]
+    /// New profile description.
+    pub description: Option<String>,
+    #[argp(option)]
+    /// Profile detail as `type=value`, replaces the existing detail of that type.
+    pub detail: Vec<String>,
+    #[argp(option)]
+    /// Birthday as `YYYY-MM-DD`.
+    pub birthday: Option<String>,
+    #[argp(option)]
+    /// Cakeday as `MM-DD`.
+    pub cakeday: Option<String>,
+    #[argp(switch)]
+    /// Remove all existing details first.
+    pub clear: bool,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for ProfileSet {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with setting profile.");
+        let url = get_url(&format!("/api/accounts/v1/profile-only/id/{}", creds.id)).await;
+        let res: V1Response = get(&url).await?;
+        let mut profile = match res {
+            V1Response::ProfileOnly { profile } => profile,
+            res => {
+                v1_handle(&res).unwrap();
+                unexpected_response("ProfileOnly", res);
+                unreachable!()
+            }
+        };
+        self.apply(&mut profile);
+
+        let body = V1SetProfile {
+            token: creds.token.clone(),
+            profile,
+        };
+
+        let url = get_url("/api/accounts/v1/set-profile").await;
+
+        let res: V1Response = post(&url, body).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
+
+impl ProfileSet {
+    /// Applies the changes to `profile`, a detail replaces the existing detail of its type.
+    pub fn apply(&self, profile: &mut ProfileCustomisable) {
+        if let Some(description) = &self.description {
+            profile.description = description.clone();
+        }
+
+        if self.clear {
+            profile.details.clear();
+        }
+        for detail in self.details() {
+            profile
+                .details
+                .retain(|existing| mem::discriminant(existing) != mem::discriminant(&detail));
+            profile.details.push(detail);
+        }
+    }
+
+    pub fn details(&self) -> Vec<ProfileDetail> {
+        let mut out = self
+            .detail
+            .iter()
+            .map(|detail| match detail.split_once('=') {
+                // detail types go by the names the server uses for them
+                Some((r#type, value)) => {
+                    let value = value.trim().to_string();
+                    match r#type.trim().to_lowercase().as_str() {
+                        "location" => ProfileDetail::Location(value),
+                        "occupation" => ProfileDetail::Occupation(value),
+                        "company" => ProfileDetail::Company(value),
+                        "school" => ProfileDetail::School(value),
+                        "email" => ProfileDetail::Email(value),
+                        "website" => ProfileDetail::Website(value),
+                        "github" => ProfileDetail::Github(value),
+                        _ => {
+                            invalid_argument(
+                                "detail",
+                                &format!("`{}` is not a text detail type", r#type.trim()),
+                            );
+                            unreachable!()
+                        }
+                    }
+                }
+                None => {
+                    invalid_argument("detail", "expected `type=value`");
+                    unreachable!()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(birthday) = &self.birthday {
+            let date = parse_date("birthday", birthday, "%Y-%m-%d");
+            out.push(ProfileDetail::BirthDay(BirthDay {
+                day: date.day() as u8,
+                month: date.month() as u8,
+                year: date.year() as u16,
+            }))
+        }
+
+        if let Some(cakeday) = &self.cakeday {
+            // any leap year so 02-29 is allowed
+            let date = parse_date("cakeday", &format!("2000-{cakeday}"), "%Y-%m-%d");
+            out.push(ProfileDetail::CakeDay(CakeDay {
+                day: date.day() as u8,
+                month: date.month() as u8,
+            }))
+        }
+
+        out
+    }
+}
+
+fn parse_date(arg: &str, value: &str, format: &str) -> NaiveDate {
+    match NaiveDate::parse_from_str(value, format) {
+        Ok(date) => date,
+        Err(e) => {
+            invalid_argument(arg, &e.to_string());
+            unreachable!()
+        }
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "reset-pfp")]
+/// Reset your profile picture to the default.
+pub struct ProfileResetPfp {}
+
+#[async_trait::async_trait]
+impl CommandTrait for ProfileResetPfp {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with resetting profile picture.");
+        let body = V1TokenOnly {
+            token: creds.token.clone(),
+        };
+
+        let url = get_url("/api/accounts/v1/reset-pfp").await;
+
+        let res: V1Response = post(&url, body).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "set-pfp")]
+/// Upload a new profile picture.
+pub struct ProfileSetPfp {
+    #[argp(positional)]
+    /// Path to the image.
+    pub image: String,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for ProfileSetPfp {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with setting profile picture.");
+        let url = get_url(&format!("/api/accounts/v1/set-pfp/{}", creds.token)).await;
+
+        let res: V1Response = upload(&url, &PathBuf::from(&self.image)).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
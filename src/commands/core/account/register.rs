@@ -7,7 +7,7 @@ use log::*;
 
 use crate::{
     exit_codes::loggedin_not_allowed,
-    functions::{get_url, post, v1_handle},
+    functions::{get_url, instance_url, post, prompt, read_pw_confirm, v1_handle},
     CREDS, INSTANCE,
 };
 
@@ -19,30 +19,18 @@ pub struct Register {
     #[argp(positional)]
     /// Invite code to server.
     pub invite: Option<String>,
-    #[argp(
-        option,
-        default = "crate::functions::prompt_sync(\"Username\")",
-        short = 'u'
-    )]
+    #[argp(option, short = 'u')]
     /// Identify yourself.
-    pub username: String,
-    #[argp(
-        option,
-        default = "crate::functions::prompt_sync(\"Email\")",
-        short = 'e'
-    )]
+    pub username: Option<String>,
+    #[argp(option, short = 'e')]
     /// Email for verification.
-    pub email: String,
-    #[argp(
-        option,
-        default = "crate::functions::prompt_sync(\"Instance\")",
-        short = 'i'
-    )]
-    /// Instance domain or IP.
-    pub instance: String,
-    #[argp(option, default = "crate::functions::read_pw_confirm()", short = 'p')]
+    pub email: Option<String>,
+    #[argp(option, short = 'i')]
+    /// Instance url, the scheme defaults to https if left out.
+    pub instance: Option<String>,
+    #[argp(option, short = 'p')]
     /// You will be prompted to enter your password securely if you skip this option.
-    pub password: String,
+    pub password: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -53,12 +41,27 @@ impl CommandTrait for Register {
         }
 
         trace!("Not logged in, proceeding with registration.");
-        unsafe { INSTANCE.set(self.instance.clone()).unwrap() };
+        let username = match &self.username {
+            Some(username) => username.clone(),
+            None => prompt("Username").await,
+        };
+        let email = match &self.email {
+            Some(email) => email.clone(),
+            None => prompt("Email").await,
+        };
+        let instance = match &self.instance {
+            Some(instance) => instance.clone(),
+            None => prompt("Instance").await,
+        };
+        unsafe { INSTANCE.set(instance_url(&instance)).unwrap() };
 
         let body = V1All3 {
-            username: self.username.clone(),
-            email: self.email.clone(),
-            password: self.password.clone(),
+            username,
+            email,
+            password: self
+                .password
+                .clone()
+                .unwrap_or_else(|| read_pw_confirm("password")),
         };
         let mut url = get_url("/api/accounts/v1/create").await;
         if let Some(invite) = &self.invite {
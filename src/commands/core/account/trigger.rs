@@ -0,0 +1,112 @@
+use std::error::Error;
+
+use argp::FromArgs;
+use command_macro::CommandTrait;
+use command_macro_derive::Command;
+use goodmorning_bindings::services::v1::V1Response;
+use log::*;
+
+use crate::functions::{get, get_url, get_url_instance, v1_handle};
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "trigger")]
+/// Inspect and use triggers, such as email verification links.
+pub struct Trigger {
+    #[argp(subcommand)]
+    pub subcommand: TriggerSubcommands,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum TriggerSubcommands {
+    Peek(TriggerPeek),
+    Run(TriggerRun),
+    Revoke(TriggerRevoke),
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for Trigger {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        self.subcommand.run().await
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "peek")]
+/// Show what a trigger does without running it.
+pub struct TriggerPeek {
+    #[argp(positional)]
+    /// Trigger ID, or the link containing it.
+    pub id: String,
+    #[argp(option, short = 'i')]
+    /// Instance of the trigger, defaults to the logged in instance.
+    pub instance: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for TriggerPeek {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        trace!("Proceeding with peeking trigger.");
+        trigger("peek", &self.id, &self.instance).await
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "run")]
+/// Run a trigger.
+pub struct TriggerRun {
+    #[argp(positional)]
+    /// Trigger ID, or the link containing it.
+    pub id: String,
+    #[argp(option, short = 'i')]
+    /// Instance of the trigger, defaults to the logged in instance.
+    pub instance: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for TriggerRun {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        trace!("Proceeding with running trigger.");
+        trigger("use", &self.id, &self.instance).await
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "revoke")]
+/// Revoke a trigger so it can no longer be run.
+pub struct TriggerRevoke {
+    #[argp(positional)]
+    /// Trigger ID, or the link containing it.
+    pub id: String,
+    #[argp(option, short = 'i')]
+    /// Instance of the trigger, defaults to the logged in instance.
+    pub instance: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for TriggerRevoke {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        trace!("Proceeding with revoking trigger.");
+        trigger("revoke", &self.id, &self.instance).await
+    }
+}
+
+async fn trigger(action: &str, id: &str, instance: &Option<String>) -> Result<(), Box<dyn Error>> {
+    // links sent by email end with the trigger id
+    let id = id.trim_end_matches('/').rsplit('/').next().unwrap();
+    let path = format!("/api/triggers/v1/{action}/{id}");
+    let url = match instance {
+        Some(instance) => get_url_instance(&path, instance),
+        None => get_url(&path).await,
+    };
+
+    let res: V1Response = get(&url).await?;
+    v1_handle(&res)?;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::error::Error;
+
+use argp::FromArgs;
+use command_macro::CommandTrait;
+use goodmorning_bindings::services::v1::{V1DirTreeItem, V1DirTreeNode, V1Response, V1TokenOnly};
+use log::*;
+
+use crate::{
+    exit_codes::{loggedin_only, unexpected_response},
+    functions::{account_to_string, filesize, get, get_url, post, v1_handle},
+    CREDS,
+};
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "whoami")]
+/// Show the logged in account.
+pub struct Whoami {}
+
+#[async_trait::async_trait]
+impl CommandTrait for Whoami {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with showing account.");
+        let body = V1TokenOnly {
+            token: creds.token.clone(),
+        };
+
+        let url = get_url("/api/accounts/v1/profile").await;
+
+        let res: V1Response = post(&url, body).await?;
+        let account = match res {
+            V1Response::Profile { account, .. } => account,
+            res => {
+                v1_handle(&res).unwrap();
+                unexpected_response("Profile", res);
+                unreachable!()
+            }
+        };
+
+        let url = get_url(&format!("/api/storage/v1/tree/{}/", creds.token)).await;
+
+        let res: V1Response = get(&url).await?;
+        let used = match res {
+            V1Response::Tree { content } => storage_used(&content),
+            res => {
+                v1_handle(&res).unwrap();
+                unexpected_response("Tree", res);
+                unreachable!()
+            }
+        };
+
+        println!(
+            "{}\n  Instance: {}\n  Storage used: {}",
+            account_to_string(&account),
+            creds.instance,
+            filesize(used)
+        );
+
+        Ok(())
+    }
+}
+
+fn storage_used(node: &V1DirTreeNode) -> u64 {
+    match &node.content {
+        V1DirTreeItem::File { size, .. } => *size,
+        V1DirTreeItem::Dir { content } => content.iter().map(storage_used).sum(),
+    }
+}
@@ -1,13 +1,19 @@
-use std::error::Error;
+use std::{error::Error, path::Path};
 
 use argp::FromArgs;
+use chrono::Utc;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::{V1Render, V1Response};
+use command_macro_derive::Command;
+use goodmorning_bindings::services::v1::{V1PathOnly, V1Render, V1Response};
 use log::*;
 
 use crate::{
-    exit_codes::loggedin_only,
-    functions::{get_url, post, v1_handle},
+    exit_codes::{
+        file_not_found, invalid_argument, loggedin_only, missing_argument, unexpected_response,
+    },
+    functions::{
+        get, get_url, post, run_job, upload_stream, v1_handle, zip_dir_body, GREY, RESET_COLOUR,
+    },
     CREDS,
 };
 
@@ -17,41 +23,224 @@ use crate::{
 /// Render a Minecraft world using BlueMap.
 pub struct Render {
     #[argp(positional)]
-    /// Original path of the zipped map.
-    pub from: String,
+    /// Original path of the zipped map, or the world directory with --local.
+    pub from: Option<String>,
     #[argp(positional)]
     /// Target path of the rendered map.
-    pub to: String,
-    #[argp(positional)]
-    /// Rendering preset to use.
-    pub preset: String,
+    pub to: Option<String>,
+    #[argp(option, short = 'p')]
+    /// Rendering preset to use (uses default preset if left empty).
+    pub preset: Option<String>,
+    #[argp(switch, short = 'l')]
+    /// Zip and upload a local world directory before rendering it.
+    pub local: bool,
+    #[argp(switch)]
+    /// Delete the world uploaded with --local once the render is done (it is always deleted if
+    /// the render fails).
+    pub clean: bool,
+    #[argp(switch)]
+    /// Return the job id once the task is queued instead of waiting for it.
+    pub detach: bool,
+    #[argp(switch)]
+    /// Show the place of the task in the job queue while waiting for it (the global `--wait`
+    /// only waits out cooldowns).
+    pub queue_position: bool,
+    #[argp(subcommand)]
+    pub subcommand: Option<RenderSubcommands>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum RenderSubcommands {
+    Presets(RenderPresets),
 }
 
 #[async_trait::async_trait]
 impl CommandTrait for Render {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(subcommand) = &self.subcommand {
+            return subcommand.run().await;
+        }
+
         let creds = unsafe { CREDS.get_mut().unwrap() };
         if !creds.is_loggedin() {
             loggedin_only()
         }
 
         trace!("Logged in, proceeding with rendering task.");
+        if self.clean && !self.local {
+            invalid_argument("clean", "only worlds uploaded with --local can be cleaned");
+        }
+        if self.clean && self.detach {
+            invalid_argument("clean", "cannot clean up after a detached task");
+        }
+        if self.detach && self.queue_position {
+            invalid_argument("queue-position", "a detached task is not waited for");
+        }
+
+        let (from, to) = match (&self.from, &self.to) {
+            (Some(from), Some(to)) => (from, to.trim_matches('/')),
+            (None, _) => {
+                missing_argument("from");
+                unreachable!()
+            }
+            (_, None) => {
+                missing_argument("to");
+                unreachable!()
+            }
+        };
 
-        let from = self.from.trim_matches('/');
-        let to = self.to.trim_matches('/');
+        let (default, all) = presets().await?;
+        let preset = match &self.preset {
+            Some(preset) if !all.contains(preset) => {
+                invalid_argument(
+                    "preset",
+                    &format!("no preset named `{preset}`, run `render presets` to list presets"),
+                );
+                unreachable!()
+            }
+            Some(preset) => preset.clone(),
+            None => default,
+        };
+        debug!("Rendering with preset {preset}");
+
+        let uploaded = if self.local {
+            Some(upload_world(Path::new(from), to).await?)
+        } else {
+            None
+        };
 
         let body = V1Render {
-            from: from.to_string(),
+            from: uploaded
+                .clone()
+                .unwrap_or_else(|| from.trim_matches('/').to_string()),
             to: to.to_string(),
-            preset: self.preset.clone(),
+            preset,
             token: creds.token.clone(),
         };
 
         let url = get_url("/api/blue/v1/render").await;
 
-        let res: V1Response = post(&url, body).await?;
-        v1_handle(&res)?;
+        // the error is held while cleaning up, which a boxed error cannot be across an await
+        let res = run_job(&url, body, self.detach, self.queue_position)
+            .await
+            .map_err(|e| e.to_string());
+        if let Ok(Some(res)) = &res {
+            v1_handle(res)?;
+        }
+
+        // an uploaded world is of no use once the render it was uploaded for is done or failed
+        let queued = matches!(res, Ok(None));
+        let rendered = matches!(res, Ok(Some(V1Response::BlueRendered { .. })));
+        if let Some(archive) = uploaded.filter(|_| !queued && (self.clean || !rendered)) {
+            delete_world(&archive).await?;
+        }
+
+        res?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "presets")]
+/// List available rendering presets.
+pub struct RenderPresets {}
+
+#[async_trait::async_trait]
+impl CommandTrait for RenderPresets {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let (default, all) = presets().await?;
+        for preset in all.iter() {
+            if preset == &default {
+                println!("{preset} {GREY}(default){RESET_COLOUR}");
+            } else {
+                println!("{preset}");
+            }
+        }
 
         Ok(())
     }
 }
+
+/// The default preset and all available presets.
+async fn presets() -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let url = get_url("/api/blue/v1/presets").await;
+
+    let res: V1Response = get(&url).await?;
+    match res {
+        V1Response::BluePresets { default, all } => Ok((default, all)),
+        res => {
+            v1_handle(&res).unwrap();
+            unexpected_response("BluePresets", res);
+            unreachable!()
+        }
+    }
+}
+
+/// Names of files Minecraft changes while the world is open, which are left out of uploads.
+fn is_volatile(name: &str) -> bool {
+    name == "session.lock" || name.ends_with(".tmp") || name.ends_with("_old")
+}
+
+/// Zips the world directory at `dir` while uploading it next to `to`, returns the remote path
+/// of the archive.
+///
+/// The archive gets a path of its own, so neither an existing file nor a render of the same
+/// world in progress is touched, and the upload fails rather than overwrite anything.
+async fn upload_world(dir: &Path, to: &str) -> Result<String, Box<dyn Error>> {
+    if !dir.exists() {
+        file_not_found(dir);
+    }
+    if !dir.is_dir() {
+        invalid_argument(
+            "from",
+            &format!("`{}` is not a world directory", dir.to_string_lossy()),
+        );
+    }
+
+    println!("Uploading world...");
+    let creds = unsafe { CREDS.get().unwrap() };
+    let path = format!("{to}.{}.zip", Utc::now().timestamp_millis());
+    let url = get_url(&format!("/api/storage/v1/upload/{}/{path}", creds.token)).await;
+    let name = path.rsplit('/').next().unwrap();
+    let res: V1Response =
+        match upload_stream(&url, name, zip_dir_body(dir.to_path_buf(), is_volatile)).await {
+            Ok(res) => res,
+            Err(e) => {
+                // whatever part of the archive made it to the server is not needed
+                let _ = delete_world(&path).await;
+                return Err(e.into());
+            }
+        };
+
+    match res {
+        V1Response::FileItemCreated => Ok(path),
+        res => {
+            v1_handle(&res)?;
+            unexpected_response("FileItemCreated", res);
+            unreachable!()
+        }
+    }
+}
+
+/// Deletes a world uploaded by `upload_world`.
+async fn delete_world(path: &str) -> Result<(), Box<dyn Error>> {
+    trace!("Deleting uploaded world at {path}.");
+    let creds = unsafe { CREDS.get().unwrap() };
+    let body = V1PathOnly {
+        token: creds.token.clone(),
+        path: path.to_string(),
+    };
+
+    let url = get_url("/api/storage/v1/delete").await;
+
+    let res: V1Response = post(&url, body).await?;
+    match res {
+        V1Response::FileItemDeleted => println!("Uploaded world has been deleted."),
+        res => v1_handle(&res)?,
+    }
+
+    Ok(())
+}
@@ -7,7 +7,10 @@ use log::*;
 
 use crate::{
     exit_codes::{loggedin_only, missing_argument},
-    functions::{get, get_url, get_url_instance, post, v1_handle},
+    functions::{
+        get, get_url, get_url_instance, instance_url, map_redirect_url, post, v1_handle,
+        with_map_action, GREY, RESET_COLOUR,
+    },
     CREDS,
 };
 
@@ -25,6 +28,9 @@ pub struct Exist {
     #[argp(option, short = 'i')]
     /// Instance the user is on
     pub instance: Option<String>,
+    #[argp(switch)]
+    /// Show the redirect instead of following it into a map
+    pub no_follow: bool,
 }
 
 #[async_trait::async_trait]
@@ -39,8 +45,11 @@ impl CommandTrait for Exist {
 
         let path = self.path.trim_matches('/');
 
-        let res: V1Response = if self.id.is_some_and(|id| id != creds.id)
-            || self.instance.as_ref().is_some_and(|i| i != &creds.instance)
+        let (url, res) = if self.id.is_some_and(|id| id != creds.id)
+            || self
+                .instance
+                .as_ref()
+                .is_some_and(|i| instance_url(i) != creds.instance)
         {
             let url = get_url_instance(
                 &format!(
@@ -53,16 +62,27 @@ impl CommandTrait for Exist {
                 ),
                 self.instance.as_ref().unwrap(),
             );
-            get(&url).await
+            let res: V1Response = get(&url).await?;
+            (url, res)
         } else {
             let url = get_url("/api/storage/v1/exists").await;
             let body = V1PathOnly {
                 token: creds.token.clone(),
                 path: path.to_string(),
             };
-            post(&url, body).await
-        }?;
+            let res: V1Response = post(&url, body).await?;
+            (url, res)
+        };
 
+        let res = match res {
+            // the redirect is to the item itself, the map is asked whether it exists instead
+            V1Response::WithinMap { redirect } if !self.no_follow => {
+                let url = with_map_action(&map_redirect_url(&url, &redirect), "exists");
+                eprintln!("{GREY}Checking the item within the map at {redirect}.{RESET_COLOUR}");
+                get(&url).await?
+            }
+            res => res,
+        };
         v1_handle(&res)?;
 
         Ok(())
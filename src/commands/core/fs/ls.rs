@@ -7,7 +7,7 @@ use log::*;
 
 use crate::{
     exit_codes::missing_argument,
-    functions::{get, get_url, get_url_instance, v1_handle},
+    functions::{follow_map, get, get_url, get_url_instance, instance_url, v1_handle},
     BASE_PATH, CREDS, FULLPATH,
 };
 
@@ -28,6 +28,9 @@ pub struct Ls {
     #[argp(switch, short = 'f')]
     /// Display full item path
     pub full: bool,
+    #[argp(switch)]
+    /// Show the redirect instead of following it into a map
+    pub no_follow: bool,
 }
 
 #[async_trait::async_trait]
@@ -48,7 +51,10 @@ impl CommandTrait for Ls {
 
         let url = if !creds.is_loggedin()
             || self.id.is_some_and(|id| id != creds.id)
-            || self.instance.as_ref().is_some_and(|i| i != &creds.instance)
+            || self
+                .instance
+                .as_ref()
+                .is_some_and(|i| instance_url(i) != creds.instance)
         {
             get_url_instance(
                 &format!(
@@ -77,7 +83,10 @@ impl CommandTrait for Ls {
             })
             .unwrap();
 
-        let res: V1Response = get(&url).await?;
+        let mut res: V1Response = get(&url).await?;
+        if !self.no_follow {
+            res = follow_map(&url, res).await?;
+        }
         v1_handle(&res)?;
 
         Ok(())
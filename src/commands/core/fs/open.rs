@@ -1,14 +1,22 @@
-use std::{error::Error, fs, path::PathBuf, time::UNIX_EPOCH};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use argp::FromArgs;
-use chrono::Utc;
 use command_macro::CommandTrait;
+use goodmorning_bindings::services::v1::V1Response;
 
 use log::*;
 
 use crate::{
-    exit_codes::missing_argument,
-    functions::{download, get_url, get_url_instance},
+    exit_codes::{missing_argument, unexpected_response},
+    functions::{
+        download_or_map, follow_map, get_cached, get_url, get_url_instance, instance_url,
+        map_redirect_url, v1_handle, Download, GREY, MAX_MAP_REDIRECTS, RESET_COLOUR,
+    },
+    structs::{CacheEntry, CacheIndex, Validators},
     CREDS, MAX_AGE,
 };
 
@@ -29,6 +37,9 @@ pub struct Open {
     /// Refetch item even if it is still fresh
     #[argp(switch, short = 'f')]
     pub fetch: bool,
+    #[argp(switch)]
+    /// Show the redirect instead of following it into a map
+    pub no_follow: bool,
 }
 
 #[async_trait::async_trait]
@@ -46,11 +57,16 @@ impl CommandTrait for Open {
 
         let path = self.path.trim_matches('/');
 
-        let (url, user_path) = if !creds.is_loggedin()
+        let (parent_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+        let (url, diritems_url, user_path) = if !creds.is_loggedin()
             || self.id.is_some_and(|id| id != creds.id)
-            || self.instance.as_ref().is_some_and(|i| i != &creds.instance)
+            || self
+                .instance
+                .as_ref()
+                .is_some_and(|i| instance_url(i) != creds.instance)
         {
-            let instance = self.instance.as_ref().unwrap_or(&creds.instance);
+            let instance = instance_url(self.instance.as_ref().unwrap_or(&creds.instance));
             let id = self.id.unwrap_or_else(|| {
                 missing_argument("id");
                 unreachable!()
@@ -58,23 +74,27 @@ impl CommandTrait for Open {
             (
                 get_url_instance(
                     &format!("/api/usercontent/v1/file/id/{id}/{path}",),
-                    self.instance.as_ref().unwrap_or(&creds.instance),
+                    &instance,
+                ),
+                get_url_instance(
+                    &format!("/api/usercontent/v1/diritems/id/{id}/{parent_path}"),
+                    &instance,
                 ),
-                PathBuf::from(instance).join(id.to_string()),
+                instance_dir(&instance).join(id.to_string()),
             )
         } else {
             (
                 get_url(&format!("/api/storage/v1/file/{}/{}", creds.token, path)).await,
-                PathBuf::from(&creds.instance).join(creds.id.to_string()),
+                get_url(&format!(
+                    "/api/storage/v1/diritems/{}/{}",
+                    creds.token, parent_path
+                ))
+                .await,
+                instance_dir(&creds.instance).join(creds.id.to_string()),
             )
         };
 
-        let output = dirs::cache_dir()
-            .unwrap()
-            .join(env!("CARGO_PKG_NAME"))
-            .join("fs")
-            .join(user_path)
-            .join(path);
+        let output = CacheIndex::dir().join("fs").join(user_path).join(path);
 
         let parent = output.parent().unwrap();
         if !parent.exists() {
@@ -85,22 +105,63 @@ impl CommandTrait for Open {
             fs::create_dir_all(parent)?;
         }
 
-        if self.fetch
-            || !output.exists()
-            || Utc::now().timestamp() as u64
-                - output
-                    .metadata()?
-                    .modified()?
-                    .duration_since(UNIX_EPOCH)?
-                    .as_secs()
-                > *MAX_AGE.get().unwrap()
-        {
-            println!("Fetching file...");
-            download(&url, &output).await?;
-            println!("File fetched to {}", output.to_string_lossy())
+        let cached = if output.exists() {
+            CacheIndex::read(|index| index.get(&output).cloned())
         } else {
+            None
+        };
+
+        if !self.fetch
+            && cached
+                .as_ref()
+                .is_some_and(|entry| entry.age() <= *MAX_AGE.get().unwrap())
+        {
             println!("Not fetching file as it is still fresh.");
-            println!("Cached file located at {}", output.to_string_lossy())
+            println!("Cached file located at {}", output.to_string_lossy());
+            CacheIndex::update(|index| index.touch(&output));
+        } else {
+            let (last_modified, size) =
+                match Self::remote_meta(&diritems_url, name, !self.no_follow).await? {
+                    Some(meta) => meta,
+                    None => return Ok(()),
+                };
+
+            let unchanged = !self.fetch
+                && cached.as_ref().is_some_and(|entry| {
+                    entry.last_modified == last_modified && entry.size == size
+                });
+
+            let fetched = if unchanged {
+                Download::NotModified
+            } else {
+                println!("Fetching file...");
+                let validators = match &cached {
+                    Some(entry) if !self.fetch => entry.validators.clone(),
+                    _ => Validators::default(),
+                };
+                self.fetch_file(&url, &output, &validators).await?
+            };
+
+            match fetched {
+                Download::Saved(validators) => {
+                    CacheIndex::update(|index| {
+                        index.insert(
+                            &output,
+                            CacheEntry::new(last_modified, size).with_validators(validators),
+                        )
+                    });
+                    println!("File fetched to {}", output.to_string_lossy())
+                }
+                Download::NotModified => {
+                    println!("Not fetching file as it is unchanged on remote.");
+                    println!("Cached file located at {}", output.to_string_lossy());
+                    CacheIndex::update(|index| index.refresh(&output));
+                }
+                Download::WithinMap { redirect } => {
+                    v1_handle(&V1Response::WithinMap { redirect })?;
+                    return Ok(());
+                }
+            }
         }
 
         if let Err(e) = open::that_detached(&output) {
@@ -119,3 +180,69 @@ impl CommandTrait for Open {
         Ok(())
     }
 }
+
+impl Open {
+    /// Last modified and size of a remote file, looked up from its parent directory.
+    ///
+    /// `None` if the file is within a map and the redirect is not followed.
+    async fn remote_meta(
+        diritems_url: &str,
+        name: &str,
+        follow: bool,
+    ) -> Result<Option<(u64, u64)>, Box<dyn Error>> {
+        let mut res: V1Response = get_cached(diritems_url).await?;
+        if follow {
+            res = follow_map(diritems_url, res).await?;
+        }
+        let content = match res {
+            V1Response::DirContent { content } => content,
+            V1Response::WithinMap { .. } => {
+                v1_handle(&res)?;
+                return Ok(None);
+            }
+            _ => {
+                v1_handle(&res)?;
+                unexpected_response("DirContent", res);
+                unreachable!()
+            }
+        };
+
+        match content
+            .iter()
+            .find(|item| item.is_file && item.name == name)
+        {
+            Some(item) => Ok(Some((item.last_modified, item.size))),
+            None => Err(format!("file `{name}` not found in remote directory").into()),
+        }
+    }
+
+    /// Downloads the file, and follows it into a map if the server answers with a redirect.
+    ///
+    /// The redirect is only returned if it is not followed, it is never written to `output`.
+    async fn fetch_file(
+        &self,
+        url: &str,
+        output: &Path,
+        validators: &Validators,
+    ) -> Result<Download, Box<dyn Error>> {
+        let mut url = url.to_string();
+        let mut fetched = download_or_map(&url, output, validators).await?;
+        for _ in 0..MAX_MAP_REDIRECTS {
+            let redirect = match &fetched {
+                Download::WithinMap { redirect } if !self.no_follow => redirect,
+                _ => break,
+            };
+
+            url = map_redirect_url(&url, redirect);
+            eprintln!("{GREY}Fetching file within the map at {redirect}.{RESET_COLOUR}");
+            fetched = download_or_map(&url, output, &Validators::default()).await?;
+        }
+
+        Ok(fetched)
+    }
+}
+
+/// Cache directory of an instance, `https://example.com` is kept under `https/example.com`.
+fn instance_dir(instance: &str) -> PathBuf {
+    PathBuf::from(instance.replacen("://", "/", 1))
+}
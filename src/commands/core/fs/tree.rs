@@ -7,7 +7,7 @@ use log::trace;
 
 use crate::{
     exit_codes::missing_argument,
-    functions::{get, get_url, get_url_instance, v1_handle},
+    functions::{follow_map, get, get_url, get_url_instance, instance_url, v1_handle},
     BASE_PATH, CREDS, FULLPATH, FULL_PATH,
 };
 
@@ -28,6 +28,9 @@ pub struct Tree {
     #[argp(switch, short = 'f')]
     /// Display full item path
     pub full: bool,
+    #[argp(switch)]
+    /// Show the redirect instead of following it into a map
+    pub no_follow: bool,
 }
 
 #[async_trait::async_trait]
@@ -49,7 +52,10 @@ impl CommandTrait for Tree {
 
         let url = if !creds.is_loggedin()
             || self.id.is_some_and(|id| id != creds.id)
-            || self.instance.as_ref().is_some_and(|i| i != &creds.instance)
+            || self
+                .instance
+                .as_ref()
+                .is_some_and(|i| instance_url(i) != creds.instance)
         {
             get_url_instance(
                 &format!(
@@ -74,7 +80,10 @@ impl CommandTrait for Tree {
             BASE_PATH.set(String::new()).unwrap();
         }
 
-        let res: V1Response = get(&url).await?;
+        let mut res: V1Response = get(&url).await?;
+        if !self.no_follow {
+            res = follow_map(&url, res).await?;
+        }
 
         v1_handle(&res)?;
 
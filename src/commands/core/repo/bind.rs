@@ -11,7 +11,7 @@ use tokio::fs;
 
 use crate::{
     exit_codes::{bad_head_json, file_not_found},
-    functions::{get_string, url_domain},
+    functions::{get_string, instance_url, url_instance},
     structs::{FsHead, GmIgnoreDefault, Repo},
     CREDS,
 };
@@ -42,8 +42,8 @@ impl CommandTrait for Bind {
         }
 
         let creds = unsafe { CREDS.get().unwrap() };
-        let dom = url_domain(&self.url).to_string();
-        let same_dom = dom == creds.instance;
+        let dom = url_instance(&self.url);
+        let same_dom = creds.is_loggedin() && dom == instance_url(&creds.instance);
 
         let (res, code) = get_string(&self.url, true, same_dom).await?;
 
@@ -12,7 +12,9 @@ use tokio::fs;
 
 use crate::{
     exit_codes::{bad_head_json, output_path_occupied, sync_failed, unexpected_response},
-    functions::{get, get_string, get_url_instance, url_domain, v1_handle, DEFAULT_VIS},
+    functions::{
+        get, get_string, get_url_instance, instance_url, url_instance, v1_handle, DEFAULT_VIS,
+    },
     structs::{FsHead, GmIgnoreDefault, Repo, TreeDiff},
     BASE_PATH, CREDS, OUTPUT_DIR,
 };
@@ -38,8 +40,8 @@ impl CommandTrait for Clone {
         stdout.flush().unwrap();
 
         let creds = unsafe { CREDS.get().unwrap() };
-        let dom = url_domain(&self.url).to_string();
-        let same_dom = dom == creds.instance;
+        let dom = url_instance(&self.url);
+        let same_dom = creds.is_loggedin() && dom == instance_url(&creds.instance);
 
         let (res, code) = get_string(&self.url, true, same_dom).await?;
 
@@ -120,7 +122,7 @@ impl CommandTrait for Clone {
         BASE_PATH.set(head.path.to_string()).unwrap();
         OUTPUT_DIR.set(output.clone()).unwrap();
 
-        if let Err(e) = diff.pull(&head, url.split('/').next().unwrap(), own).await {
+        if let Err(e) = diff.pull(&head, &dom, own, &tree).await {
             println!();
             sync_failed(e);
         }
@@ -14,7 +14,7 @@ use crate::{
     exit_codes::{
         missing_repo_json, repo_conflict, repo_not_found, sync_failed, unexpected_response,
     },
-    functions::{get, get_url_instance, ignore_tree, v1_handle},
+    functions::{get_cached, get_url_instance, ignore_tree, v1_handle},
     structs::{FsHead, Repo, TreeDiff},
     BASE_PATH, CREDS, OUTPUT_DIR,
 };
@@ -64,7 +64,7 @@ impl CommandTrait for Pull {
             },
             &repo.instance,
         );
-        let res: V1Response = get(&url).await?;
+        let res: V1Response = get_cached(&url).await?;
         println!("\rResolving objects, done.");
         let remote_current = match res {
             V1Response::Tree { content } => content,
@@ -76,12 +76,14 @@ impl CommandTrait for Pull {
         };
         let fs_current = ignore_tree(&output).await;
 
-        let remote_diff = TreeDiff::cmp(&repo.trees.remote, &remote_current);
+        let mut remote_diff = TreeDiff::cmp(&repo.trees.remote, &remote_current);
         if remote_diff.is_empty() {
             println!("You are up to date.");
             return Ok(());
         }
 
+        remote_diff.detect_remote_moves(&repo.trees.remote, &remote_current);
+
         let fs_diff = TreeDiff::cmp(&repo.trees.fs, &fs_current);
 
         let conflicts = remote_diff.conflict(&fs_diff);
@@ -103,11 +105,15 @@ impl CommandTrait for Pull {
             id: repo.user,
         };
 
-        if let Err(e) = remote_diff.pull(&head, &repo.instance, own).await {
+        if let Err(e) = remote_diff
+            .pull(&head, &repo.instance, own, &remote_current)
+            .await
+        {
             sync_failed(e);
         }
         repo.trees.remote = remote_current;
         remote_diff.apply(&mut repo.trees.fs);
+        repo.trees.rehash(&output, &remote_diff).await;
         repo.save(&output).await;
 
         println!("All done, you are now up to date.");
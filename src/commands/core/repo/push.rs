@@ -15,7 +15,7 @@ use crate::{
         missing_repo_json, permission_denied, repo_conflict, repo_not_found, sync_failed,
         unexpected_response,
     },
-    functions::{get, get_url_instance, ignore_tree, v1_handle},
+    functions::{get, get_url_instance, hash_files, ignore_tree, v1_handle},
     structs::{FsHead, Repo, TreeDiff},
     BASE_PATH, CREDS, OUTPUT_DIR,
 };
@@ -77,13 +77,28 @@ impl CommandTrait for Push {
         let fs_current = ignore_tree(&output).await;
 
         let remote_diff = TreeDiff::cmp(&repo.trees.remote, &remote_current);
-        let fs_diff = TreeDiff::cmp(&repo.trees.fs, &fs_current);
+        let mut fs_diff = TreeDiff::cmp(&repo.trees.fs, &fs_current);
 
         if fs_diff.is_empty() {
             println!("Remote is up to date.");
             return Ok(());
         }
 
+        if !fs_diff.deleted.is_empty() && !fs_diff.created.is_empty() {
+            trace!("Hashing created files to detect moves.");
+            let created = hash_files(
+                &output,
+                fs_diff.created.iter().map(|item| item.path.as_str()),
+            )
+            .await;
+            let synced = &repo.trees.hashes;
+            fs_diff.detect_moves(&repo.trees.fs, &fs_current, |from, _, to, _| {
+                synced
+                    .get(from)
+                    .is_some_and(|hash| created.get(to) == Some(hash))
+            });
+        }
+
         let conflicts = remote_diff.conflict(&fs_diff);
 
         if !conflicts.conflicts.is_empty() {
@@ -116,6 +131,7 @@ impl CommandTrait for Push {
         // };
         fs_diff.apply(&mut repo.trees.remote);
         repo.trees.fs = fs_current;
+        repo.trees.rehash(&output, &fs_diff).await;
         repo.save(&output).await;
 
         println!("All done, updates are pushed to remote.");
@@ -0,0 +1,236 @@
+use std::{error::Error, path::Path};
+
+use argp::FromArgs;
+use command_macro::CommandTrait;
+use goodmorning_bindings::services::v1::{V1Compile, V1Error, V1Response};
+use log::*;
+use tokio::{fs, task::JoinSet};
+
+use crate::{
+    commands::core::Push,
+    exit_codes::{
+        bad_build_manifest, build_failed, invalid_argument, loggedin_only, missing_repo_json,
+    },
+    functions::{
+        download, get_url, parse_latex_log, post_waiting, v1_handle, Severity, GREEN, RED,
+        RESET_COLOUR,
+    },
+    structs::{BuildManifest, BuildTarget, CompileTarget, Repo},
+    CREDS,
+};
+
+use super::local_paths;
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "build")]
+/// Push the current repo and compile the targets in its gmbuild.toml.
+pub struct Build {
+    #[argp(positional)]
+    /// Name of the target to build (builds all targets if left empty).
+    pub target: Option<String>,
+    #[argp(switch, short = 'f')]
+    /// Overwrite conflict files when pushing.
+    pub force: bool,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for Build {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with building targets.");
+        let root = match Repo::find(Path::new(".")).await? {
+            Some(root) => root,
+            None => {
+                missing_repo_json();
+                unreachable!()
+            }
+        };
+
+        let repo = Repo::load(&root).await;
+        let base = match repo.path.trim_matches('/').strip_prefix("tex") {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_matches('/'),
+            _ => {
+                bad_build_manifest("the repo is not in /tex, only files in /tex can be compiled");
+                unreachable!()
+            }
+        };
+
+        let manifest = BuildManifest::load(&root).await;
+        let targets = match &self.target {
+            Some(name) => {
+                let targets = manifest
+                    .targets
+                    .into_iter()
+                    .filter(|target| &target.name == name)
+                    .collect::<Vec<_>>();
+                if targets.is_empty() {
+                    invalid_argument(
+                        "target",
+                        &format!("no target named `{name}` in {}", BuildManifest::FILE_NAME),
+                    );
+                }
+                targets
+            }
+            None => manifest.targets,
+        };
+
+        // check every target before pushing, so a typo does not leave a half done build
+        let jobs = targets
+            .into_iter()
+            .map(|target| {
+                let path = format!("{base}/{}", target.source.trim_matches('/'))
+                    .trim_matches('/')
+                    .to_string();
+                match CompileTarget::resolve(
+                    &path,
+                    target.from.as_deref(),
+                    target.to.as_deref(),
+                    target.compiler.as_deref(),
+                ) {
+                    Ok(compile) => (target, path, compile),
+                    Err(e) => {
+                        bad_build_manifest(&format!("target `{}`: {e}", target.name));
+                        unreachable!()
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Push {
+            force: self.force,
+            output: root.clone(),
+        }
+        .run()
+        .await?;
+
+        println!("Compiling {} target(s)...", jobs.len());
+        let results = compile_all(&jobs, &creds.token).await?;
+
+        let mut rows = Vec::new();
+        let mut failed = 0;
+        for ((target, path, _), res) in jobs.iter().zip(results) {
+            match res {
+                V1Response::TexCompiled { newpath, .. } => {
+                    let output = match &target.output {
+                        Some(output) => {
+                            let local = root.join(output);
+                            if let Some(parent) = local.parent() {
+                                fs::create_dir_all(parent).await?;
+                            }
+                            let url = get_url(&format!(
+                                "/api/storage/v1/file/{}/tex/{}",
+                                creds.token,
+                                newpath.trim_start_matches('/')
+                            ))
+                            .await;
+                            download(&url, &local).await?;
+                            output.clone()
+                        }
+                        None => format!("/tex/{newpath}"),
+                    };
+                    rows.push((target, true, output));
+                }
+                res => {
+                    failed += 1;
+                    println!("\nTarget `{}` failed:", target.name);
+                    rows.push((target, false, report(&res, path).await?));
+                }
+            }
+        }
+
+        let width = rows
+            .iter()
+            .map(|(target, ..)| target.name.len())
+            .chain(["Target".len()])
+            .max()
+            .unwrap();
+        println!("\n{:<width$}  {:<8}  Output", "Target", "Status");
+        for (target, compiled, output) in rows {
+            let status = if compiled {
+                format!("{GREEN}{:<8}{RESET_COLOUR}", "compiled")
+            } else {
+                format!("{RED}{:<8}{RESET_COLOUR}", "failed")
+            };
+            println!("{:<width$}  {status}  {output}", target.name);
+        }
+
+        if failed != 0 {
+            build_failed(failed);
+        }
+
+        Ok(())
+    }
+}
+
+/// Most compile requests a build has waiting on the server at once.
+const MAX_PARALLEL: usize = 4;
+
+/// Sends the compile requests of all targets, up to `MAX_PARALLEL` at a time, and returns the
+/// responses in the same order.
+async fn compile_all(
+    jobs: &[(BuildTarget, String, CompileTarget)],
+    token: &str,
+) -> Result<Vec<V1Response>, Box<dyn Error>> {
+    let url = get_url("/api/compile/v1/simple").await;
+    let mut results = jobs.iter().map(|_| None).collect::<Vec<_>>();
+    let mut pending = jobs.iter().enumerate();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < MAX_PARALLEL {
+            let Some((i, (_, path, compile))) = pending.next() else {
+                break;
+            };
+            let body = V1Compile {
+                from: compile.from.into(),
+                to: compile.to.into(),
+                compiler: compile.compiler.map(Into::into),
+                token: token.to_string(),
+                path: path.clone(),
+            };
+            let url = url.clone();
+            running.spawn(async move { (i, post_waiting::<V1Response, _>(&url, body).await) });
+        }
+
+        match running.join_next().await {
+            Some(joined) => {
+                let (i, res) = joined?;
+                results[i] = Some(res?);
+            }
+            None => break,
+        }
+    }
+
+    Ok(results.into_iter().map(Option::unwrap).collect())
+}
+
+/// Prints why a target failed, and returns a short summary for the table.
+async fn report(res: &V1Response, path: &str) -> Result<String, Box<dyn Error>> {
+    if let V1Response::Error {
+        kind: V1Error::CompileError { content },
+    } = res
+    {
+        let mut diagnostics = parse_latex_log(content);
+        if !diagnostics.is_empty() {
+            local_paths(&mut diagnostics, path).await?;
+            diagnostics.iter().for_each(|item| println!("  {item}"));
+
+            let errors = diagnostics
+                .iter()
+                .filter(|item| item.severity == Severity::Error)
+                .count();
+            return Ok(format!(
+                "{errors} error(s), {} warning(s)",
+                diagnostics.len() - errors
+            ));
+        }
+    }
+
+    v1_handle(res)?;
+    Ok("-".to_string())
+}
@@ -1,13 +1,24 @@
-use std::{error::Error, ffi::OsStr, path::PathBuf};
+use std::{
+    env,
+    error::Error,
+    path::{Component, Path, PathBuf},
+};
 
 use argp::FromArgs;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::{Compiler, FromFormat, ToFormat, V1Compile, V1Response};
+use goodmorning_bindings::services::v1::{V1Compile, V1Error, V1Response};
 use log::*;
 
 use crate::{
-    exit_codes::{loggedin_only, missing_argument, unknown_compiler, unknown_format},
-    functions::{get_url, post, v1_handle},
+    exit_codes::{
+        compile_failed, invalid_argument, loggedin_only, missing_argument, unexpected_response,
+        unknown_compiler, unknown_format, unsupported_compile,
+    },
+    functions::{
+        download, get_url, parse_latex_log, run_job, v1_handle, Diagnostic, BLUE, GREY,
+        RESET_COLOUR, YELLOW,
+    },
+    structs::{CompileTarget, FormatError, Repo, COMPILERS, FROM_FORMATS, TO_FORMATS},
     CREDS,
 };
 
@@ -18,100 +29,270 @@ use crate::{
 pub struct Compile {
     #[argp(positional)]
     /// The path of source file (omit beginning `/tex`).
-    pub path: String,
+    pub path: Option<String>,
     #[argp(option, short = 'f')]
     /// Format to compile from (inferred from file extension if left empty).
     pub from: Option<String>,
     #[argp(option, short = 't')]
-    /// Format to compile to (inferred from file extension if left empty).
+    /// Format to compile to (inferred from the source format if left empty).
     pub to: Option<String>,
     #[argp(option, short = 'c')]
     /// Compiler used for compiling (uses default compiler if left empty).
     pub compiler: Option<String>,
+    #[argp(switch)]
+    /// Return the job id once the task is queued instead of waiting for it.
+    pub detach: bool,
+    #[argp(switch)]
+    /// Show the place of the task in the job queue while waiting for it (the global `--wait`
+    /// only waits out cooldowns).
+    pub queue_position: bool,
+    #[argp(option, short = 'd')]
+    /// Download the compiled file to this local path once done.
+    pub download: Option<String>,
+    #[argp(switch)]
+    /// Print compile errors and warnings as JSON.
+    pub json: bool,
+    #[argp(switch)]
+    /// List supported formats and compilers.
+    pub list: bool,
 }
 
 #[async_trait::async_trait]
 impl CommandTrait for Compile {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
+        if self.list {
+            list();
+            return Ok(());
+        }
+
         let creds = unsafe { CREDS.get_mut().unwrap() };
         if !creds.is_loggedin() {
             loggedin_only()
         }
 
         trace!("Logged in, proceeding with compiling file.");
+        if self.detach && self.download.is_some() {
+            invalid_argument("download", "cannot download a detached task");
+        }
+        if self.detach && self.queue_position {
+            invalid_argument("queue-position", "a detached task is not waited for");
+        }
 
-        let from = match self.from.as_ref() {
-            None => match PathBuf::from(&self.path)
-                .extension()
-                .unwrap_or(OsStr::new(""))
-                .to_str()
-                .unwrap()
-            {
-                "md" => FromFormat::Markdown,
-                "tex" => FromFormat::Latex,
-                _ => {
-                    missing_argument("from");
-                    unreachable!()
-                }
-            },
-            Some(s) => match s.as_str() {
-                "markdown" | "md" => FromFormat::Markdown,
-                "tex" | "lt" | "latex" => FromFormat::Latex,
-                _ => {
-                    unknown_format(s);
-                    unreachable!()
-                }
-            },
-        };
-
-        let to = match self.to.as_ref() {
-            None => match PathBuf::from(&self.path)
-                .extension()
-                .unwrap_or(OsStr::new(""))
-                .to_str()
-                .unwrap()
-            {
-                "md" => ToFormat::Html,
-                "tex" => ToFormat::Pdf,
-                _ => {
-                    missing_argument("to");
-                    unreachable!()
-                }
-            },
-            Some(s) => match s.as_str() {
-                "html" => ToFormat::Html,
-                "pdf" => ToFormat::Pdf,
-                _ => {
-                    unknown_format(s);
-                    unreachable!()
-                }
-            },
+        let path = match &self.path {
+            Some(path) => path.trim_matches('/'),
+            None => {
+                missing_argument("path");
+                unreachable!()
+            }
         };
-
-        let compiler = self.compiler.as_ref().map(|s| match s.as_str() {
-            "pulldown cmark" | "cmark" => Compiler::PulldownCmark,
-            "pdflatex" => Compiler::Pdflatex,
-            s => {
-                unknown_compiler(s);
+        let target = match CompileTarget::resolve(
+            path,
+            self.from.as_deref(),
+            self.to.as_deref(),
+            self.compiler.as_deref(),
+        ) {
+            Ok(target) => target,
+            Err(FormatError::UnknownFormat(format)) => {
+                unknown_format(&format);
                 unreachable!()
             }
-        });
+            Err(FormatError::UnknownCompiler(compiler)) => {
+                unknown_compiler(&compiler);
+                unreachable!()
+            }
+            Err(FormatError::MissingFrom) => {
+                missing_argument("from");
+                unreachable!()
+            }
+            Err(FormatError::MissingTo) => {
+                missing_argument("to");
+                unreachable!()
+            }
+            Err(e) => {
+                unsupported_compile(&e.to_string());
+                unreachable!()
+            }
+        };
+        debug!("Compiling with {target:?}");
 
-        let path = self.path.trim_matches('/');
         let body = V1Compile {
-            from,
-            to,
-            compiler,
+            from: target.from.into(),
+            to: target.to.into(),
+            compiler: target.compiler.map(Into::into),
             token: creds.token.clone(),
             path: path.to_string(),
         };
 
-        println!("Running compile task...");
+        if !self.json {
+            println!("Running compile task...");
+        }
         let url = get_url("/api/compile/v1/simple").await;
 
-        let res: V1Response = post(&url, body).await?;
-        v1_handle(&res)?;
+        let res = match run_job(&url, body, self.detach, self.queue_position).await? {
+            Some(res) => res,
+            None => return Ok(()),
+        };
+
+        match &res {
+            V1Response::Error {
+                kind: V1Error::CompileError { content },
+            } => {
+                let mut diagnostics = parse_latex_log(content);
+                if self.json || !diagnostics.is_empty() {
+                    local_paths(&mut diagnostics, path).await?;
+                    if self.json {
+                        println!("{}", serde_json::to_string(&diagnostics)?);
+                    } else {
+                        diagnostics.iter().for_each(|item| println!("{item}"));
+                    }
+                } else {
+                    v1_handle(&res)?;
+                }
+                compile_failed();
+            }
+            V1Response::TexCompiled { .. } if self.json => println!("[]"),
+            // errors other than a failed compile, so scripts can still tell what went wrong
+            V1Response::Error { kind } if self.json => {
+                println!("{}", serde_json::to_string(kind)?);
+                unexpected_response("TexCompiled", res.clone());
+                unreachable!()
+            }
+            _ => v1_handle(&res)?,
+        }
+
+        if let (Some(local), V1Response::TexCompiled { newpath, .. }) = (&self.download, &res) {
+            let url = get_url(&format!(
+                "/api/storage/v1/file/{}/tex/{}",
+                creds.token,
+                newpath.trim_start_matches('/')
+            ))
+            .await;
+            download(&url, &PathBuf::from(local)).await?;
+            if !self.json {
+                println!("Compiled file downloaded to {local}.");
+            }
+        }
 
         Ok(())
     }
 }
+
+fn list() {
+    let formats = FROM_FORMATS
+        .iter()
+        .map(|info| (info.name, info.aliases, info.extensions))
+        .chain(
+            TO_FORMATS
+                .iter()
+                .map(|info| (info.name, info.aliases, info.extensions)),
+        )
+        .map(|(name, aliases, extensions)| {
+            let aliases = if aliases.is_empty() {
+                String::new()
+            } else {
+                format!(" {GREY}({}){RESET_COLOUR}", aliases.join(", "))
+            };
+            format!(
+                "  {BLUE}{name}{aliases} {YELLOW}{}{RESET_COLOUR}",
+                extensions
+                    .iter()
+                    .map(|ext| format!(".{ext}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let compilers = COMPILERS
+        .iter()
+        .enumerate()
+        .map(|(i, info)| {
+            let from = FROM_FORMATS
+                .iter()
+                .find(|format| format.format == info.from);
+            let to = TO_FORMATS.iter().find(|format| format.format == info.to);
+            let default = if COMPILERS[..i]
+                .iter()
+                .any(|other| other.from == info.from && other.to == info.to)
+            {
+                ""
+            } else {
+                " (default)"
+            };
+            format!(
+                "  {BLUE}{}{RESET_COLOUR} {} -> {}{GREY}{default}{RESET_COLOUR}",
+                info.name,
+                from.unwrap().name,
+                to.unwrap().name
+            )
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "Formats:\n{}\n\nCompilers:\n{}",
+        formats.join("\n"),
+        compilers.join("\n")
+    );
+}
+
+/// Points diagnostics at the files they are in.
+///
+/// Files in the log are relative to the compiled file on the remote, they become local paths if
+/// the working directory is in a repo bound to that remote directory, or remote paths otherwise.
+pub async fn local_paths(
+    diagnostics: &mut [Diagnostic],
+    compiled: &str,
+) -> Result<(), Box<dyn Error>> {
+    let creds = unsafe { CREDS.get().unwrap() };
+    let root = Repo::find(Path::new(".")).await?;
+    let repo = match root {
+        Some(root) => {
+            let repo = Repo::load(&root).await;
+            if repo.user == creds.id && repo.instance == creds.instance {
+                Some((root, PathBuf::from(repo.path.trim_matches('/'))))
+            } else {
+                debug!("Repo is bound to another account, keeping remote paths.");
+                None
+            }
+        }
+        None => None,
+    };
+    let cwd = env::current_dir()?.canonicalize()?;
+    let dir = PathBuf::from("tex").join(compiled);
+    let dir = dir.parent().unwrap_or(Path::new("tex"));
+
+    for item in diagnostics.iter_mut() {
+        let file = match &item.file {
+            Some(file) if !file.starts_with('/') => file,
+            _ => continue,
+        };
+
+        // resolve `.` and `..` without touching the local file system
+        let mut remote = PathBuf::new();
+        for component in dir.join(file).components() {
+            match component {
+                Component::ParentDir => {
+                    remote.pop();
+                }
+                Component::Normal(part) => remote.push(part),
+                _ => {}
+            }
+        }
+
+        let local = repo.as_ref().and_then(|(root, base)| {
+            let local = root.join(remote.strip_prefix(base).ok()?);
+            Some(
+                local
+                    .strip_prefix(&cwd)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(local),
+            )
+        });
+        item.file = Some(match local {
+            Some(local) => local.to_string_lossy().to_string(),
+            None => format!("/{}", remote.to_string_lossy()),
+        });
+    }
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
 mod compile;
 pub use compile::*;
+mod build;
+pub use build::*;
 mod publish;
 pub use publish::*;
 mod publishes;
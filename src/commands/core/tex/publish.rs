@@ -2,12 +2,17 @@ use std::error::Error;
 
 use argp::FromArgs;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::{V1Publish, V1Response, V1UpdatePublish};
+use command_macro_derive::Command;
+use goodmorning_bindings::services::v1::{
+    V1EditPublish, V1Publish, V1Response, V1Unpublish, V1UpdatePublish,
+};
 use log::*;
 
 use crate::{
     exit_codes::{loggedin_only, missing_argument},
-    functions::{get_url, post, v1_handle},
+    functions::{
+        get_url, post, publish, publish_to_string, published_file_url, publisher, v1_handle,
+    },
     CREDS,
 };
 
@@ -18,7 +23,7 @@ use crate::{
 pub struct Publish {
     #[argp(positional)]
     /// Remote path of file (omit `/tex/`).
-    pub path: String,
+    pub path: Option<String>,
     #[argp(option, short = 't')]
     /// Title of article
     pub title: Option<String>,
@@ -28,11 +33,26 @@ pub struct Publish {
     #[argp(option, short = 'u')]
     /// Update an exsisting article
     pub update: Option<u64>,
+    #[argp(subcommand)]
+    pub subcommand: Option<PublishSubcommands>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum PublishSubcommands {
+    Rm(PublishRm),
+    Show(PublishShow),
+    Edit(PublishEdit),
 }
 
 #[async_trait::async_trait]
 impl CommandTrait for Publish {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(subcommand) = &self.subcommand {
+            return subcommand.run().await;
+        }
+
         let creds = unsafe { CREDS.get_mut().unwrap() };
         if !creds.is_loggedin() {
             loggedin_only()
@@ -40,7 +60,13 @@ impl CommandTrait for Publish {
 
         trace!("Logged in, proceeding with publishing article.");
 
-        let path = self.path.trim_matches('/');
+        let path = match &self.path {
+            Some(path) => path.trim_matches('/'),
+            None => {
+                missing_argument("path");
+                unreachable!()
+            }
+        };
 
         let res: V1Response = match self.update {
             Some(id) => {
@@ -88,3 +114,115 @@ impl CommandTrait for Publish {
         Ok(())
     }
 }
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "rm")]
+/// Unpublish an article.
+pub struct PublishRm {
+    #[argp(positional)]
+    /// ID of the article.
+    pub id: i64,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for PublishRm {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with unpublishing article.");
+        let body = V1Unpublish {
+            token: creds.token.clone(),
+            id: self.id,
+        };
+
+        let url = get_url("/api/publish/v1/unpublish").await;
+
+        let res: V1Response = post(&url, body).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "show")]
+/// Show details of a published article.
+pub struct PublishShow {
+    #[argp(positional)]
+    /// ID of the article.
+    pub id: i64,
+    #[argp(option, short = 'u')]
+    /// ID of user who published the article
+    pub user: Option<i64>,
+    #[argp(option, short = 'i')]
+    /// Instance the user is on
+    pub instance: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for PublishShow {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let (instance, user) = publisher(self.user, self.instance.as_deref());
+        trace!("Proceeding with showing published article.");
+
+        let item = publish(&instance, user, self.id).await?;
+        println!(
+            "{}",
+            publish_to_string(&item, &published_file_url(&instance, user, item.id))
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "edit")]
+/// Change the title or description of a published article, the file stays the same.
+pub struct PublishEdit {
+    #[argp(positional)]
+    /// ID of the article.
+    pub id: i64,
+    #[argp(option, short = 't')]
+    /// New title of article
+    pub title: Option<String>,
+    #[argp(option, short = 'd')]
+    /// New description of article
+    pub description: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for PublishEdit {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let creds = unsafe { CREDS.get_mut().unwrap() };
+        if !creds.is_loggedin() {
+            loggedin_only()
+        }
+
+        trace!("Logged in, proceeding with editing article.");
+        if self.title.is_none() && self.description.is_none() {
+            missing_argument("title");
+        }
+
+        // both fields are sent, so the one left out is kept as it is
+        let current = publish(&creds.instance, creds.id, self.id).await?;
+        let body = V1EditPublish {
+            token: creds.token.clone(),
+            id: self.id,
+            title: self.title.clone().unwrap_or(current.title),
+            desc: self.description.clone().unwrap_or(current.desc),
+        };
+
+        let url = get_url("/api/publish/v1/edit-publish").await;
+
+        let res: V1Response = post(&url, body).await?;
+        v1_handle(&res)?;
+
+        Ok(())
+    }
+}
@@ -1,14 +1,21 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
 
 use argp::FromArgs;
 use command_macro::CommandTrait;
-use goodmorning_bindings::services::v1::V1Response;
+use command_macro_derive::Command;
 use log::*;
+use tokio::fs;
 
 use crate::{
-    exit_codes::missing_argument,
-    functions::{get, get_url_instance, v1_handle},
-    CREDS, INSTANCE, USER_ID,
+    exit_codes::{invalid_argument, missing_argument},
+    functions::{
+        all_publishes, download, download_conditional, publish, published_file_url, publisher,
+        publishes_page, publishes_to_string,
+    },
+    structs::{MirroredPublish, PublishMirror},
 };
 
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -25,34 +32,207 @@ pub struct Publishes {
     #[argp(option, short = 'p', default = "1")]
     /// Page to view
     pub page: u64,
+    #[argp(switch, short = 'a')]
+    /// List items from all pages
+    pub all: bool,
+    #[argp(subcommand)]
+    pub subcommand: Option<PublishesSubcommands>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum PublishesSubcommands {
+    Get(PublishesGet),
 }
 
 #[async_trait::async_trait]
 impl CommandTrait for Publishes {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
-        let creds = unsafe { CREDS.get_mut().unwrap() };
-        if !creds.is_loggedin() {
-            trace!("Not logged in, proceeding with listing published items.");
-            if self.instance.is_none() {
-                missing_argument("instance")
-            }
-            if self.id.is_none() {
-                missing_argument("id")
-            }
+        if let Some(subcommand) = &self.subcommand {
+            return subcommand.run().await;
+        }
+
+        if self.all && self.page != 1 {
+            invalid_argument("page", "cannot view a single page when listing all pages");
+        }
+
+        let (instance, id) = publisher(self.id, self.instance.as_deref());
+        trace!("Proceeding with listing published items.");
+
+        let items = if self.all {
+            all_publishes(&instance, id).await?
+        } else {
+            publishes_page(&instance, id, self.page).await?.0
+        };
+
+        if items.is_empty() && !self.all && self.page != 1 {
+            println!("Nothing on page {}.", self.page);
+        } else if items.is_empty() {
+            println!("Nothing has been published.");
         } else {
-            trace!("Logged in, proceeding with listing published items.");
+            println!("{}", publishes_to_string(&items, &instance, id));
         }
 
-        let instance = self.instance.as_ref().unwrap_or(&creds.instance);
-        unsafe { *INSTANCE.get_mut().unwrap() = instance.clone() };
-        let id = self.id.unwrap_or(creds.id);
-        unsafe { USER_ID.set(id).unwrap() };
+        Ok(())
+    }
+}
 
-        let url = get_url_instance(&format!("/api/publish/v1/publishes/id/{id}"), instance);
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "get")]
+/// Download published items.
+pub struct PublishesGet {
+    #[argp(positional)]
+    /// ID of the item (omit when getting all items).
+    pub item: Option<i64>,
+    #[argp(option, short = 'u')]
+    /// ID of user who published the item
+    pub user: Option<i64>,
+    #[argp(option, short = 'i')]
+    /// Instance the user is on
+    pub instance: Option<String>,
+    #[argp(option, short = 'o')]
+    /// Path to download to, or the directory to mirror into with --all
+    pub output: Option<PathBuf>,
+    #[argp(switch, short = 'a')]
+    /// Mirror all items of the user into a directory, skipping ones that are up to date
+    pub all: bool,
+    #[argp(switch)]
+    /// Remove mirrored items that are no longer published, only with --all
+    pub prune: bool,
+}
 
-        let res: V1Response = get(&url).await?;
-        v1_handle(&res)?;
+#[async_trait::async_trait]
+impl CommandTrait for PublishesGet {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let (instance, user) = publisher(self.user, self.instance.as_deref());
+
+        if self.prune && !self.all {
+            invalid_argument("prune", "only a mirror made with --all can be pruned");
+        }
+        if self.all {
+            if self.item.is_some() {
+                invalid_argument("item", "cannot get a single item when getting all items");
+            }
+
+            trace!("Proceeding with mirroring published items.");
+            let dir = self.output.clone().unwrap_or(PathBuf::from("."));
+            return mirror(&instance, user, &dir, self.prune).await;
+        }
+
+        let id = match self.item {
+            Some(id) => id,
+            None => {
+                missing_argument("item");
+                unreachable!()
+            }
+        };
+
+        trace!("Proceeding with downloading published item.");
+        let item = publish(&instance, user, id).await?;
+        let file = format!("{id}.{}", item.ext);
+        let path = match &self.output {
+            Some(path) if path.is_dir() => path.join(&file),
+            Some(path) => path.clone(),
+            None => PathBuf::from(&file),
+        };
+
+        download(&published_file_url(&instance, user, id), &path).await?;
+        println!(
+            "Downloaded [{id}] {} to {}.",
+            item.title,
+            path.to_string_lossy()
+        );
 
         Ok(())
     }
 }
+
+/// Downloads every item of the user into `dir`, and with `prune` removes the ones no longer
+/// published.
+///
+/// Items are checked with the validators from their last download, or by publish time if the
+/// server did not send any. The index is saved after every item, so a failed download does not
+/// lose the ones before it.
+async fn mirror(instance: &str, user: i64, dir: &Path, prune: bool) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir).await?;
+    let items = all_publishes(instance, user).await?;
+    let mut index = PublishMirror::load(dir);
+
+    let mut downloaded = 0;
+    let mut skipped = 0;
+    for item in items.iter() {
+        let file = format!("{}.{}", item.id, item.ext);
+        let path = dir.join(&file);
+
+        let previous = index.items.get(&item.id);
+        if let Some(previous) = previous.filter(|previous| previous.file != file) {
+            trace!("Format of [{}] changed, removing old copy.", item.id);
+            fs::remove_file(dir.join(&previous.file)).await.ok();
+        }
+
+        let previous = previous.filter(|previous| previous.file == file && path.exists());
+        if previous.is_some_and(|previous| {
+            previous.validators.is_empty() && previous.published == item.published
+        }) {
+            trace!("[{}] has not been republished, skipping.", item.id);
+            skipped += 1;
+            continue;
+        }
+
+        let validators = previous
+            .map(|previous| previous.validators.clone())
+            .unwrap_or_default();
+        let url = published_file_url(instance, user, item.id);
+        match download_conditional(&url, &path, &validators).await? {
+            Some(validators) => {
+                println!("Downloaded [{}] {}", item.id, item.title);
+                downloaded += 1;
+                index.items.insert(
+                    item.id,
+                    MirroredPublish {
+                        file,
+                        published: item.published,
+                        validators,
+                    },
+                );
+                index.save(dir)?;
+            }
+            None => {
+                trace!("[{}] not modified, skipping.", item.id);
+                skipped += 1;
+                if let Some(entry) = index.items.get_mut(&item.id) {
+                    entry.published = item.published;
+                    index.save(dir)?;
+                }
+            }
+        }
+    }
+
+    let stale = index
+        .items
+        .keys()
+        .filter(|id| !items.iter().any(|item| item.id == **id))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut removed = 0;
+    if prune {
+        for id in stale.iter() {
+            let entry = index.items.remove(id).unwrap();
+            fs::remove_file(dir.join(&entry.file)).await.ok();
+            index.save(dir)?;
+            println!("Removed [{id}], it is no longer published.");
+            removed += 1;
+        }
+    } else if !stale.is_empty() {
+        println!(
+            "{} item(s) are no longer published, run with --prune to remove them.",
+            stale.len()
+        );
+    }
+
+    println!("{downloaded} downloaded, {skipped} up to date, {removed} removed.");
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::{cmp::Reverse, error::Error};
+
+use argp::FromArgs;
+use chrono::{Local, TimeZone};
+use command_macro::CommandTrait;
+use command_macro_derive::Command;
+use log::*;
+
+use crate::{
+    functions::{filesize, BLUE, GREY, PURPLE, RESET_COLOUR, YELLOW},
+    structs::CacheIndex,
+    MAX_CACHE_SIZE,
+};
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "cache")]
+/// Inspect and manage cache.
+pub struct Cache {
+    #[argp(subcommand)]
+    pub subcommand: CacheSubcommands,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs, Command)]
+#[argp(subcommand)]
+pub enum CacheSubcommands {
+    Ls(CacheLs),
+    Size(CacheSize),
+    Evict(CacheEvict),
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for Cache {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        self.subcommand.run().await
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "ls")]
+/// List cached items, most recently used first.
+pub struct CacheLs {}
+
+#[async_trait::async_trait]
+impl CommandTrait for CacheLs {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let index = CacheIndex::read(CacheIndex::clone);
+        let mut entries = index.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, entry)| Reverse(entry.accessed));
+
+        let sizes = entries
+            .iter()
+            .map(|(_, entry)| filesize(entry.size))
+            .collect::<Vec<_>>();
+        let longest_size = sizes.iter().map(String::len).max().unwrap_or(0);
+
+        let title = format!("{BLUE}{} items in cache{RESET_COLOUR}", entries.len());
+        let items = entries
+            .iter()
+            .zip(sizes)
+            .map(|((key, entry), size)| {
+                let accessed = Local
+                    .timestamp_opt(entry.accessed as i64, 0)
+                    .unwrap()
+                    .format("%y %b %e %H:%M");
+                format!(
+                    "{PURPLE}{size: >longest_size$} {BLUE}{accessed} {YELLOW}{key}{RESET_COLOUR}"
+                )
+            })
+            .collect::<Vec<_>>();
+
+        println!(
+            "{title}\n{GREY}{}{RESET_COLOUR}\n{}",
+            "─".repeat(items.first().unwrap_or(&title).len()),
+            items.join("\n")
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "size")]
+/// Show total size of cache.
+pub struct CacheSize {}
+
+#[async_trait::async_trait]
+impl CommandTrait for CacheSize {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let (count, total) = CacheIndex::read(|index| (index.entries.len(), index.total()));
+        println!(
+            "{} items in cache, using {} out of {}.",
+            count,
+            filesize(total),
+            filesize(*MAX_CACHE_SIZE.get().unwrap())
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(FromArgs)]
+#[argp(subcommand, name = "evict")]
+/// Evict items from cache.
+pub struct CacheEvict {
+    #[argp(positional)]
+    /// Evict items under this path in cache, or least recently used items
+    /// until cache is under the size limit if not specified.
+    pub path: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CommandTrait for CacheEvict {
+    async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let (count, size) = match &self.path {
+            Some(path) => {
+                let path = path.trim_matches('/').to_string();
+                trace!("Evicting items under `{path}`.");
+                CacheIndex::update(|index| {
+                    index.remove_where(|key, _| key == path || key.starts_with(&format!("{path}/")))
+                })
+            }
+            None => {
+                trace!("Evicting least recently used items.");
+                CacheIndex::update(|index| index.evict_to(*MAX_CACHE_SIZE.get().unwrap()))
+            }
+        };
+
+        println!(
+            "{count} files evicted, freed {} of disk space.",
+            filesize(size)
+        );
+
+        Ok(())
+    }
+}
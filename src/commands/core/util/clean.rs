@@ -1,16 +1,23 @@
-use std::{error::Error, path::Path, sync::OnceLock, time::UNIX_EPOCH};
+use std::{error::Error, fs::Metadata, path::Path, sync::OnceLock, time::Duration};
 
 use argp::FromArgs;
-use chrono::Utc;
 use command_macro::CommandTrait;
 use log::*;
 use tokio::fs::{self, DirEntry};
 
-use crate::{functions::filesize, MAX_AGE};
+use crate::{
+    functions::filesize,
+    structs::{CacheIndex, ObjectStore},
+    MAX_AGE, MAX_CACHE_SIZE,
+};
 
 static mut DELETED_SIZE: OnceLock<u64> = OnceLock::new();
 static mut DELETED_COUNT: OnceLock<u64> = OnceLock::new();
 
+/// Untracked items newer than this are left alone, they may belong to a command that has not
+/// saved the cache index yet.
+const UNTRACKED_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[derive(FromArgs, Default)]
 #[argp(subcommand, name = "clean")]
@@ -24,7 +31,7 @@ pub struct Clean {
 #[async_trait::async_trait]
 impl CommandTrait for Clean {
     async fn run(&self) -> Result<(), Box<dyn Error>> {
-        let cache = dirs::cache_dir().unwrap().join(env!("CARGO_PKG_NAME"));
+        let cache = CacheIndex::dir();
         if !cache.exists() {
             println!("Nothing in cache, skipping.");
             return Ok(());
@@ -39,6 +46,18 @@ impl CommandTrait for Clean {
 
         Self::clean_cache().await?;
 
+        // files from before the index existed are not tracked, sweep up the old ones as well
+        let index = CacheIndex::read(CacheIndex::clone);
+        for dir in [
+            cache.join("fs"),
+            cache.join("responses"),
+            ObjectStore::dir(),
+        ] {
+            if fs::try_exists(&dir).await? {
+                Self::clean_untracked(&dir, &index).await?;
+            }
+        }
+
         println!(
             "{} files deleted, freed {} of disk space.",
             unsafe { DELETED_COUNT.get().unwrap() },
@@ -50,58 +69,80 @@ impl CommandTrait for Clean {
 }
 
 impl Clean {
+    /// Evicts objects unused for longer than `max-age`, then least recently used items until the
+    /// cache fits in `max-cache-size`. Other stale items are kept as they can still be
+    /// revalidated.
     pub async fn clean_cache() -> Result<(), Box<dyn Error>> {
         unsafe {
             DELETED_SIZE.set(0).unwrap();
             DELETED_COUNT.set(0).unwrap()
         };
 
-        let cache = dirs::cache_dir().unwrap().join(env!("CARGO_PKG_NAME"));
-        if !fs::try_exists(&cache).await? {
-            return Ok(());
+        let (count, size) = CacheIndex::update(|index| {
+            index.prune();
+            let (expired_count, expired_size) = ObjectStore::expire(index, *MAX_AGE.get().unwrap());
+            let (lru_count, lru_size) = index.evict_to(*MAX_CACHE_SIZE.get().unwrap());
+
+            (expired_count + lru_count, expired_size + lru_size)
+        });
+
+        unsafe {
+            *DELETED_SIZE.get_mut().unwrap() += size;
+            *DELETED_COUNT.get_mut().unwrap() += count;
         }
 
-        Self::clean(&cache).await
+        Ok(())
     }
 
     #[async_recursion::async_recursion]
-    async fn clean(path: &Path) -> Result<(), Box<dyn Error>> {
+    async fn clean_untracked(path: &Path, index: &CacheIndex) -> Result<(), Box<dyn Error>> {
         trace!("Reading directory `{}`", path.to_string_lossy());
         let mut diritems = fs::read_dir(path).await?;
         let mut tasks = Vec::new();
 
         while let Some(entry) = diritems.next_entry().await? {
-            tasks.push(Self::clean_entry(entry));
+            tasks.push(Self::clean_entry(entry, index));
         }
 
         for task in tasks {
             task.await?;
         }
 
-        if fs::read_dir(path).await?.next_entry().await?.is_none() {
+        if fs::read_dir(path).await?.next_entry().await?.is_none()
+            && Self::settled(&fs::metadata(path).await?)
+        {
             fs::remove_dir(path).await?;
         }
 
         Ok(())
     }
 
-    async fn clean_entry(entry: DirEntry) -> Result<(), Box<dyn Error>> {
+    async fn clean_entry(entry: DirEntry, index: &CacheIndex) -> Result<(), Box<dyn Error>> {
         let metadata = entry.metadata().await?;
 
         if metadata.is_dir() {
-            Self::clean(&entry.path()).await?;
-        } else if Utc::now().timestamp() as u64
-            - metadata.modified()?.duration_since(UNIX_EPOCH)?.as_secs() as u64
-            > *MAX_AGE.get().unwrap()
-        {
+            Self::clean_untracked(&entry.path(), index).await?;
+        } else if index.get(&entry.path()).is_none() && Self::settled(&metadata) {
             unsafe {
                 *DELETED_SIZE.get_mut().unwrap() += metadata.len();
                 *DELETED_COUNT.get_mut().unwrap() += 1;
             }
-            trace!("Deleted stale file `{}`", entry.path().to_string_lossy());
+            trace!(
+                "Deleted untracked file `{}`",
+                entry.path().to_string_lossy()
+            );
             fs::remove_file(entry.path()).await?;
         }
 
         Ok(())
     }
+
+    /// Whether an untracked item was last modified longer than `UNTRACKED_GRACE` ago.
+    fn settled(metadata: &Metadata) -> bool {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > UNTRACKED_GRACE)
+    }
 }
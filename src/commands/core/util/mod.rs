@@ -2,3 +2,5 @@ mod version;
 pub use version::*;
 mod clean;
 pub use clean::*;
+mod cache;
+pub use cache::*;
@@ -4,7 +4,7 @@ use argp::FromArgs;
 use command_macro::CommandTrait;
 use command_macro_derive::Command;
 
-use crate::*;
+use crate::{functions::instance_url, *};
 
 use self::core::*;
 
@@ -20,9 +20,15 @@ pub struct TopLevel {
     /// Yes, do as I say.
     #[argp(switch, short = 'y', global)]
     pub yes: bool,
-    /// Use unencrypted http traffic instead of https.
+    /// Use unencrypted http for instances given without a scheme.
     #[argp(switch, global)]
     pub http: bool,
+    /// Wait out cooldowns and rate limits instead of failing.
+    #[argp(switch, global)]
+    pub wait: bool,
+    /// Fail on missing values instead of prompting for them.
+    #[argp(switch, global)]
+    pub no_input: bool,
 
     #[argp(subcommand)]
     pub subcommand: TopLevelSubcommands,
@@ -34,9 +40,12 @@ pub struct TopLevel {
 pub enum TopLevelSubcommands {
     Version(Version),
     Clean(Clean),
+    Cache(Cache),
     Register(Register),
     Login(Login),
     Logout(Logout),
+    Whoami(Whoami),
+    Profile(Profile),
     Regen(Regen),
     Delete(Delete),
     Status(Status),
@@ -50,6 +59,7 @@ pub enum TopLevelSubcommands {
     Access(Access),
     AccessTo(AccessTo),
     Invite(Invite),
+    Trigger(Trigger),
 
     Jobs(Jobs),
 
@@ -71,6 +81,7 @@ pub enum TopLevelSubcommands {
     Bind(Bind),
 
     Compile(Compile),
+    Build(Build),
     Publish(Publish),
     Publishes(Publishes),
 
@@ -81,6 +92,18 @@ impl TopLevel {
     pub async fn run(&self) -> Result<(), Box<dyn Error>> {
         HTTP.set(self.http).unwrap();
         YES.set(self.yes).unwrap();
+        WAIT.set(self.wait).unwrap();
+        NO_INPUT.set(self.no_input).unwrap();
+
+        // instances saved before schemes were recorded are plain domains
+        unsafe {
+            let creds = CREDS.get_mut().unwrap();
+            if creds.is_loggedin() {
+                creds.instance = instance_url(&creds.instance);
+                INSTANCE.take();
+                INSTANCE.set(creds.instance.clone()).unwrap();
+            }
+        }
 
         self.subcommand.run().await?;
 
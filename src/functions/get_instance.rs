@@ -1,6 +1,7 @@
-use crate::INSTANCE;
+use crate::{exit_codes::bad_url, CREDS, HTTP, INSTANCE};
 
 use log::*;
+use reqwest::Url;
 
 use super::prompt;
 
@@ -8,19 +9,62 @@ pub async fn get_instance() -> String {
     match unsafe { INSTANCE.get() } {
         Some(i) if !i.is_empty() => {
             trace!("Instance already contains value, skipping.");
-            i.to_string()
+            instance_url(i)
         }
         Some(_) => {
             debug!("Instance contains empty string, prompting for new value.");
-            let i = prompt("Enter instance address").await;
-            *unsafe { INSTANCE.get_mut().unwrap() } = i.trim().to_string();
+            let i = instance_url(&prompt("Instance").await);
+            *unsafe { INSTANCE.get_mut().unwrap() } = i.clone();
             i
         }
         None => {
             debug!("Instance is empty, prompting for new value.");
-            let i = prompt("Enter instance address").await;
-            *unsafe { INSTANCE.get_mut().unwrap() } = i.trim().to_string();
+            let i = instance_url(&prompt("Instance").await);
+            *unsafe { INSTANCE.get_mut().unwrap() } = i.clone();
             i
         }
     }
 }
+
+/// Base url of an instance, with scheme and without trailing slash.
+///
+/// Instances stored before schemes were recorded are plain domains,
+/// these get `http` or `https` depending on the `--http` switch.
+pub fn instance_url(instance: &str) -> String {
+    let instance = instance.trim().trim_end_matches('/');
+    if instance.starts_with("http://") || instance.starts_with("https://") {
+        instance.to_string()
+    } else if *HTTP.get().unwrap() {
+        format!("http://{instance}")
+    } else {
+        format!("https://{instance}")
+    }
+}
+
+/// Instance a url belongs to.
+///
+/// That is the logged in instance if the url is under it, which allows instances with a path
+/// prefix, otherwise the scheme, host and port of the url.
+pub fn url_instance(url: &str) -> String {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed,
+        Ok(_) => {
+            bad_url("protocol must be http or https", url);
+            unreachable!()
+        }
+        Err(e) => {
+            bad_url(&e.to_string(), url);
+            unreachable!()
+        }
+    };
+
+    let creds = unsafe { CREDS.get().unwrap() };
+    if creds.is_loggedin() {
+        let logged_in = instance_url(&creds.instance);
+        if url == logged_in || url.starts_with(&format!("{logged_in}/")) {
+            return logged_in;
+        }
+    }
+
+    parsed.origin().ascii_serialization()
+}
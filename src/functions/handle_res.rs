@@ -8,10 +8,13 @@ use log::*;
 use crate::{
     functions::{diritems_tostring, jobs_to_string},
     structs::CredsConfig,
-    CREDS, INSTANCE, USER_ID,
+    CREDS, INSTANCE,
 };
 
-use super::{duration_as_string, publishes_to_string, tree_show};
+use super::{
+    account_to_string, current_publisher, duration_as_string, parse_latex_log, publish_to_string,
+    published_file_url, publishes_to_string, tree_show, trigger_to_string, value_to_string,
+};
 
 pub fn ev1_handle(err: &V1Error) -> Result<(), Box<dyn Error>> {
     debug!("Handling error {err:?}");
@@ -58,7 +61,14 @@ pub fn ev1_handle(err: &V1Error) -> Result<(), Box<dyn Error>> {
         V1Error::BirthCakeConflict => println!("You can only have one of the birthday or cakeday,\nyou don't need to tell us the same thing twice."),
         V1Error::InvalidDetail { index } => println!("You have provided an invalid detail in index {index} (0 based)\nCorrect it or remove it to resolve error."),
         V1Error::GmtOnly => println!("This action is only enabled for GM Tex, and not elsewhere."),
-        V1Error::CompileError { content } => println!("There has been a fatal compile error:\n{}", content.lines().map(|s| format!("  {s}")).collect::<Vec<_>>().join("\n")),
+        V1Error::CompileError { content } => {
+            let diagnostics = parse_latex_log(content);
+            if diagnostics.is_empty() {
+                println!("There has been a fatal compile error:\n{}", content.lines().map(|s| format!("  {s}")).collect::<Vec<_>>().join("\n"))
+            } else {
+                println!("There has been a fatal compile error:\n{}", diagnostics.iter().map(|item| format!("  {item}")).collect::<Vec<_>>().join("\n"))
+            }
+        }
         V1Error::InvalidCompileRequest => println!("The compile request you've just sent, is completely invalid.\nPlease make sure the compile target for your format exist for the specified compiler."),
         V1Error::External { content } => println!("An external error occured: {content}"),
         V1Error::FeatureDisabled => println!("This feature is disabled right now,\ntry again later."),
@@ -76,7 +86,7 @@ pub fn v1_handle(res: &V1Response) -> Result<(), Box<dyn Error>> {
 write!(buf, "\n- {current}").unwrap();
 buf
         })),
-        V1Response::TriggerPeek { value } => todo!(),
+        V1Response::TriggerPeek { value } => println!("Trigger details:\n{}", trigger_to_string(value.clone()).lines().map(|s| format!("  {s}")).collect::<Vec<_>>().join("\n")),
         V1Response::Created { id, token, verify } => {
             println!("Account has been created,");
             let creds = unsafe { CREDS.get_mut().unwrap() };
@@ -141,12 +151,18 @@ buf
         V1Response::Exists { value: false } => println!("The requested file item does not exist."),
         V1Response::ServiceCreated => println!("Service has been enabled for account successfully."),
         V1Response::ProfileUpdated => println!("Profile details updated successfully."),
-        V1Response::Profile { profile, account } => todo!(),
-        V1Response::ProfileOnly { profile } => todo!(),
-        V1Response::PfpReset => println!("Profile details has been reset successfully."),
+        V1Response::Profile { profile, account } => {
+            println!("{}", account_to_string(account));
+            println!("{}", value_to_string(&serde_json::to_value(profile)?));
+        }
+        V1Response::ProfileOnly { profile } => println!("{}", value_to_string(&serde_json::to_value(profile)?)),
+        V1Response::PfpReset => println!("Profile picture has been reset successfully."),
         V1Response::TexCompiled { id, newpath } => println!("Compiled task completed [{id}],\nthe compiled file path is `/tex/{newpath}`"),
         V1Response::TexPublished { id } => println!("Item published with ID {id}."),
-        V1Response::TexUserPublish { value } => todo!(),
+        V1Response::TexUserPublish { value } => {
+            let (instance, user) = current_publisher();
+            println!("{}", publish_to_string(value, &published_file_url(&instance, user, value.id)))
+        }
         V1Response::Allowed => println!("Access shared with user."),
         V1Response::Disallowed => println!("Access removed from user."),
         V1Response::Access { users } if !users.is_empty() => println!("The following users have access:{}", users.iter().fold(String::new(), |mut current, user| {
@@ -160,7 +176,10 @@ buf
         })),
         V1Response::AllowedAccess { .. } => println!("No other users have shared access to you."),
         // TODO
-        V1Response::TexUserPublishes { items, .. } => println!("{}", publishes_to_string(items.as_slice(), unsafe { INSTANCE.get().unwrap() }, *unsafe { USER_ID.get().unwrap() })),
+        V1Response::TexUserPublishes { items, .. } => {
+            let (instance, user) = current_publisher();
+            println!("{}", publishes_to_string(items.as_slice(), &instance, user))
+        }
         V1Response::TexPublishUpdated => println!("Published item has been updated."),
         V1Response::WithinMap { redirect } => println!("You are trying to view items within a map at {redirect}."),
         V1Response::BlueRendered { id, newpath } => println!("Render task completed [{id}],\nthe rendered map path is `/{newpath}`"),
@@ -0,0 +1,30 @@
+use std::{collections::HashMap, error::Error, path::Path};
+
+use log::*;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+pub async fn hash_file(path: &Path) -> Result<String, Box<dyn Error>> {
+    trace!("Hashing file `{}`.", path.to_string_lossy());
+    let bytes = fs::read(path).await?;
+    Ok(format!("{:x}", Sha256::digest(bytes)))
+}
+
+/// Hashes files at paths relative to `base`, files that cannot be read are left out.
+pub async fn hash_files<'a>(
+    base: &Path,
+    paths: impl Iterator<Item = &'a str>,
+) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+
+    for path in paths {
+        match hash_file(&base.join(path)).await {
+            Ok(hash) => {
+                out.insert(path.to_string(), hash);
+            }
+            Err(e) => debug!("Could not hash `{path}`: {e}"),
+        }
+    }
+
+    out
+}
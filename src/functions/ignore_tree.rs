@@ -11,7 +11,7 @@ use ignore::gitignore::GitignoreBuilder;
 use log::*;
 use tokio::fs;
 
-use crate::exit_codes::ignore_add_failed;
+use crate::{exit_codes::ignore_add_failed, structs::BuildManifest};
 
 pub const DEFAULT_VIS: V1Visibility = V1Visibility {
     inherited: true,
@@ -25,6 +25,12 @@ pub async fn ignore_tree(path: &Path) -> V1DirTreeNode {
     );
     let mut builder = GitignoreBuilder::new(path);
     builder.add_line(None, ".gmrepo.json").unwrap();
+    // compiled files are downloaded into the repo by `build`, they are not sources
+    for output in BuildManifest::outputs(path).await {
+        if let Err(e) = builder.add_line(None, &format!("/{}", output.trim_start_matches('/'))) {
+            debug!("Cannot ignore build output `{output}`: {e}");
+        }
+    }
     V1DirTreeNode {
         name: path
             .file_name()
@@ -0,0 +1,124 @@
+use std::{error::Error, io::Write, time::Duration};
+
+use goodmorning_bindings::services::v1::{V1Job, V1Response, V1TokenOnly};
+use log::*;
+use serde::Serialize;
+
+use crate::{exit_codes::unexpected_response, CREDS};
+
+use super::{get_url, post, v1_handle};
+
+/// Seconds between refreshes when watching or waiting for jobs.
+pub const POLL_INTERVAL: u64 = 2;
+
+/// Currently running and queued jobs of the logged in account.
+pub async fn jobs() -> Result<(Vec<V1Job>, Vec<V1Job>), Box<dyn Error>> {
+    let creds = unsafe { CREDS.get().unwrap() };
+    let body = V1TokenOnly {
+        token: creds.token.clone(),
+    };
+
+    let url = get_url("/api/jobs/v1/jobs").await;
+
+    let res: V1Response = post(&url, body).await?;
+    match res {
+        V1Response::Jobs { current, queue } => Ok((current, queue)),
+        res => {
+            v1_handle(&res).unwrap();
+            unexpected_response("Jobs", res);
+            unreachable!()
+        }
+    }
+}
+
+/// Polls the job list until the job with `id` has left it.
+pub async fn wait_job(id: u64) -> Result<(), Box<dyn Error>> {
+    loop {
+        let (current, queue) = jobs().await?;
+        if !current.iter().chain(queue.iter()).any(|job| job.id == id) {
+            return Ok(());
+        }
+
+        trace!("Job [{id}] still running or queued.");
+        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL)).await;
+    }
+}
+
+/// Time between looks at the job list while the job of a request has not shown up yet.
+const SPOT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Sends a request that creates a job, the server responds once the job is done.
+///
+/// With `detach` the id of the job is printed as soon as it shows up in the job list, and `None`
+/// is returned without waiting for it. With `position` the place of the job in the queue is
+/// shown while waiting.
+pub async fn run_job<T: Serialize + Send + Sync + 'static>(
+    url: &str,
+    body: T,
+    detach: bool,
+    position: bool,
+) -> Result<Option<V1Response>, Box<dyn Error>> {
+    // jobs that are already there, the new one is the job not among them
+    let before = if detach || position {
+        let (current, queue) = jobs().await?;
+        current
+            .iter()
+            .chain(queue.iter())
+            .map(|job| job.id)
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    let url = url.to_string();
+    let mut handle = tokio::spawn(async move { post::<V1Response, _>(&url, body).await });
+    if !detach && !position {
+        return Ok(Some(handle.await??));
+    }
+
+    let mut id = None;
+    let mut last = String::new();
+    let res = loop {
+        let interval = match id {
+            Some(_) => Duration::from_secs(POLL_INTERVAL),
+            None => SPOT_INTERVAL,
+        };
+        tokio::select! {
+            res = &mut handle => break res??,
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        let (current, queue) = jobs().await?;
+        if id.is_none() {
+            id = current
+                .iter()
+                .chain(queue.iter())
+                .map(|job| job.id)
+                .find(|id| !before.contains(id));
+        }
+        let Some(id) = id else {
+            trace!("Job has not shown up in the job list yet.");
+            continue;
+        };
+
+        if detach {
+            println!("Job [{id}] has been queued.");
+            return Ok(None);
+        }
+
+        let status = match queue.iter().position(|job| job.id == id) {
+            Some(i) => format!("Job [{id}] is queued at position {}.", i + 1),
+            None => format!("Job [{id}] is running."),
+        };
+        if status != last {
+            print!("\r{status:<last_len$}", last_len = last.len());
+            std::io::stdout().flush()?;
+            last = status;
+        }
+    };
+    if !last.is_empty() {
+        println!();
+    }
+
+    Ok(Some(res))
+}
@@ -0,0 +1,228 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// A problem reported in a pdflatex log.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// File being read when the problem was reported, as written in the log.
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Error,
+    Warning,
+    /// Overfull and underfull boxes.
+    Badbox,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => f.write_fmt(format_args!("{file}:{line}: "))?,
+            (Some(file), None) => f.write_fmt(format_args!("{file}: "))?,
+            (None, Some(line)) => f.write_fmt(format_args!("line {line}: "))?,
+            (None, None) => {}
+        }
+
+        match self.severity {
+            Severity::Error => {}
+            Severity::Warning | Severity::Badbox => f.write_str("warning: ")?,
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+/// Parses the errors, warnings and bad boxes out of a pdflatex log.
+///
+/// The file of each problem is tracked through the parentheses pdflatex prints when it opens
+/// and closes files, so it is a best guess when the log wraps a file name across lines.
+pub fn parse_latex_log(log: &str) -> Vec<Diagnostic> {
+    let lines = log.lines().collect::<Vec<_>>();
+    let mut out = Vec::new();
+    // `None` for parentheses that do not open a file
+    let mut files: Vec<Option<String>> = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let file = files.iter().rev().find_map(Clone::clone);
+
+        if let Some(message) = line.strip_prefix("! ") {
+            let (line, end) = error_context(&lines, i);
+            out.push(Diagnostic {
+                file,
+                line,
+                severity: Severity::Error,
+                message: message.trim().to_string(),
+            });
+            i = end;
+        } else if let Some((file, line, message)) = file_line_error(line) {
+            out.push(Diagnostic {
+                file: Some(file.to_string()),
+                line: Some(line),
+                severity: Severity::Error,
+                message: message.trim().to_string(),
+            });
+            i = error_context(&lines, i).1;
+        } else if let Some((prefix, message)) = warning(line) {
+            let mut message = match prefix {
+                "LaTeX" => message.trim().to_string(),
+                prefix => format!("{prefix}: {}", message.trim()),
+            };
+            // continuation lines are indented to line up with the message, packages also
+            // repeat their name in parentheses
+            let continued = format!("({prefix})");
+            while let Some(next) = lines.get(i + 1) {
+                let next = match next.strip_prefix(&continued) {
+                    Some(next) => next,
+                    None if next.starts_with(' ') && !next.trim().is_empty() => next,
+                    None => break,
+                };
+                message.push(' ');
+                message.push_str(next.trim());
+                i += 1;
+            }
+
+            let line = message
+                .rsplit_once("on input line ")
+                .and_then(|(_, rest)| leading_number(rest));
+            out.push(Diagnostic {
+                file,
+                line,
+                severity: Severity::Warning,
+                message,
+            });
+        } else if line.starts_with("Overfull ") || line.starts_with("Underfull ") {
+            let (message, line) = match line.split_once(" at line") {
+                Some((message, rest)) => (
+                    message
+                        .trim_end_matches(" in paragraph")
+                        .trim_end_matches(" in alignment"),
+                    leading_number(rest.trim_start_matches('s').trim_start()),
+                ),
+                None => (line, None),
+            };
+            out.push(Diagnostic {
+                file,
+                line,
+                severity: Severity::Badbox,
+                message: message.trim().to_string(),
+            });
+            // the content of the box follows up to a blank line
+            while lines.get(i + 1).is_some_and(|line| !line.is_empty()) {
+                i += 1;
+            }
+        } else {
+            track_files(line, &mut files);
+        }
+
+        i += 1;
+    }
+
+    out
+}
+
+/// Line number of the error at `start` and the last line of its context and help text.
+///
+/// The context ends with the offending input as `l.<line> <text>`, followed by the help text up
+/// to a blank line. Both are skipped as they may hold unbalanced parentheses.
+fn error_context(lines: &[&str], start: usize) -> (Option<u64>, usize) {
+    let Some((offset, line)) = lines[start + 1..]
+        .iter()
+        .take_while(|line| !line.starts_with("! "))
+        .enumerate()
+        .find_map(|(offset, line)| Some((offset, input_line(line)?)))
+    else {
+        return (None, start);
+    };
+
+    // the offending input is split over two lines at the point of the error
+    let mut end = (start + 2 + offset).min(lines.len() - 1);
+    while lines
+        .get(end + 1)
+        .is_some_and(|line| !line.trim().is_empty() && !line.starts_with("! "))
+    {
+        end += 1;
+    }
+
+    (Some(line), end)
+}
+
+fn track_files(line: &str, files: &mut Vec<Option<String>>) {
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '(' => {
+                let rest = &line[index + 1..];
+                let name = rest
+                    .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .next()
+                    .unwrap_or_default();
+                if is_file(name) {
+                    files.push(Some(name.to_string()));
+                    // skip the name so parentheses in it are not counted
+                    while chars.peek().is_some_and(|(i, _)| *i <= index + name.len()) {
+                        chars.next();
+                    }
+                } else {
+                    files.push(None);
+                }
+            }
+            ')' => {
+                files.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_file(name: &str) -> bool {
+    if name.starts_with("./") || name.starts_with("../") || name.starts_with('/') {
+        return true;
+    }
+
+    name.rsplit_once('.').is_some_and(|(stem, ext)| {
+        !stem.is_empty()
+            && ext.starts_with(|c: char| c.is_ascii_alphabetic())
+            && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// `file:line: message`, printed with `-file-line-error`.
+fn file_line_error(line: &str) -> Option<(&str, u64, &str)> {
+    let (file, rest) = line.split_once(':')?;
+    let (number, message) = rest.split_once(": ")?;
+    if !is_file(file) || file.contains(' ') {
+        return None;
+    }
+
+    Some((file, number.parse().ok()?, message))
+}
+
+/// Prefix and message of a warning line, such as `Package hyperref Warning: ...`.
+fn warning(line: &str) -> Option<(&str, &str)> {
+    let (prefix, message) = line.split_once(" Warning: ")?;
+    let name = prefix
+        .strip_prefix("Package ")
+        .or_else(|| prefix.strip_prefix("Class "))
+        .or_else(|| prefix.strip_prefix("LaTeX "))
+        .or_else(|| (prefix == "LaTeX").then_some(prefix))?;
+    Some((name, message))
+}
+
+/// Line number of an `l.<line> <text>` line.
+fn input_line(line: &str) -> Option<u64> {
+    leading_number(line.strip_prefix("l.")?)
+}
+
+fn leading_number(s: &str) -> Option<u64> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s[..end].parse().ok()
+}
@@ -22,3 +22,15 @@ mod ansi_colours;
 pub use ansi_colours::*;
 mod filesize;
 pub use filesize::*;
+mod hash_file;
+pub use hash_file::*;
+mod latex_log;
+pub use latex_log::*;
+mod jobs;
+pub use jobs::*;
+mod publishes;
+pub use publishes::*;
+mod within_map;
+pub use within_map::*;
+mod zip_dir;
+pub use zip_dir::*;
@@ -1,18 +1,28 @@
 use log::*;
 use std::io::{stdin, stdout, Write};
 
-use crate::{exit_codes::doas_failed, YES};
+use crate::{
+    exit_codes::{doas_failed, missing_argument},
+    NO_INPUT, YES,
+};
+
+/// Fails with `missing_argument` instead of prompting for `arg` when `--no-input` is set.
+pub fn require_input(arg: &str) {
+    if *NO_INPUT.get().unwrap() {
+        debug!("Input disabled, cannot prompt for {arg}.");
+        missing_argument(arg)
+    }
+}
 
 pub async fn prompt(msg: &str) -> String {
     let msg = msg.to_string();
-    tokio::runtime::Runtime::new()
-        .unwrap()
-        .spawn_blocking(move || prompt_sync(&msg))
+    tokio::task::spawn_blocking(move || prompt_sync(&msg))
         .await
         .unwrap()
 }
 
 pub fn prompt_sync(msg: &str) -> String {
+    require_input(&msg.to_lowercase());
     print!("{msg}:\n> ");
     stdout().flush().unwrap();
     let mut s = String::new();
@@ -26,6 +36,7 @@ pub async fn doasisay(msg: &str) {
         return;
     }
 
+    require_input("yes");
     if prompt(&format!("You are about to carry out `{msg}`.\nIf you understand that this is a potentially dangerous action and wish to proceed,\ntype \"Yes, do as I say\" below")).await.to_lowercase().as_str() != "yes, do as i say" {
         doas_failed()
     }
@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use goodmorning_bindings::services::v1::{V1Response, V1TexUserPublish};
+use log::*;
+
+use crate::{
+    exit_codes::{missing_argument, unexpected_response},
+    CREDS, INSTANCE, USER_ID,
+};
+
+use super::{get, get_url_instance, instance_url, v1_handle};
+
+/// Instance and id of the user whose published items are looked at, defaults to the logged in
+/// user.
+pub fn publisher(id: Option<i64>, instance: Option<&str>) -> (String, i64) {
+    let creds = unsafe { CREDS.get().unwrap() };
+    if !creds.is_loggedin() {
+        trace!("Not logged in, user and instance must be specified.");
+        if instance.is_none() {
+            missing_argument("instance")
+        }
+        if id.is_none() {
+            missing_argument("id")
+        }
+    }
+
+    let instance = instance_url(instance.unwrap_or(&creds.instance));
+    unsafe {
+        INSTANCE.take();
+        INSTANCE.set(instance.clone()).unwrap()
+    };
+    let id = id.unwrap_or(creds.id);
+    unsafe {
+        USER_ID.take();
+        USER_ID.set(id).unwrap()
+    };
+
+    (instance, id)
+}
+
+/// The user `publisher()` last picked, or the logged in user if it has not run.
+pub fn current_publisher() -> (String, i64) {
+    let creds = unsafe { CREDS.get().unwrap() };
+    let instance = unsafe { INSTANCE.get() }
+        .cloned()
+        .unwrap_or_else(|| instance_url(&creds.instance));
+    let id = unsafe { USER_ID.get() }.copied().unwrap_or(creds.id);
+    (instance, id)
+}
+
+/// Url the file of a published item is served at.
+pub fn published_file_url(instance: &str, userid: i64, id: i64) -> String {
+    get_url_instance(
+        &format!("/api/publish/v1/published-file/id/{userid}/{id}"),
+        instance,
+    )
+}
+
+/// Details of a single published item.
+pub async fn publish(
+    instance: &str,
+    userid: i64,
+    id: i64,
+) -> Result<V1TexUserPublish, Box<dyn Error>> {
+    let url = get_url_instance(
+        &format!("/api/publish/v1/publish/id/{userid}/{id}"),
+        instance,
+    );
+
+    let res: V1Response = get(&url).await?;
+    match res {
+        V1Response::TexUserPublish { value } => Ok(value),
+        res => {
+            v1_handle(&res).unwrap();
+            unexpected_response("TexUserPublish", res);
+            unreachable!()
+        }
+    }
+}
+
+/// A page of published items of a user, starting from page 1, and the page after it if there
+/// are more items.
+pub async fn publishes_page(
+    instance: &str,
+    userid: i64,
+    page: u64,
+) -> Result<(Vec<V1TexUserPublish>, Option<u64>), Box<dyn Error>> {
+    let url = get_url_instance(
+        &format!("/api/publish/v1/publishes/id/{userid}?page={page}"),
+        instance,
+    );
+
+    let res: V1Response = get(&url).await?;
+    match res {
+        V1Response::TexUserPublishes {
+            items,
+            continuation,
+        } => Ok((items, continuation)),
+        res => {
+            v1_handle(&res).unwrap();
+            unexpected_response("TexUserPublishes", res);
+            unreachable!()
+        }
+    }
+}
+
+/// All published items of a user, fetched page by page until the server says there are no more.
+pub async fn all_publishes(
+    instance: &str,
+    userid: i64,
+) -> Result<Vec<V1TexUserPublish>, Box<dyn Error>> {
+    let mut items = Vec::new();
+    let mut page = Some(1);
+    while let Some(current) = page {
+        let (mut current, next) = publishes_page(instance, userid, current).await?;
+        items.append(&mut current);
+        page = next;
+    }
+
+    Ok(items)
+}
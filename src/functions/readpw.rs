@@ -1,6 +1,9 @@
 use log::*;
 
-pub fn read_pw_confirm() -> String {
+use super::require_input;
+
+pub fn read_pw_confirm(arg: &str) -> String {
+    require_input(arg);
     loop {
         trace!("Reading password with prompt + confirm [1/2]");
         let password1 = rpassword::prompt_password("Your new password: ").unwrap();
@@ -23,11 +26,13 @@ pub fn read_pw_confirm() -> String {
 }
 
 pub fn read_pw() -> String {
+    require_input("password");
     trace!("Reading password with prompt.");
     rpassword::prompt_password("Your password: ").unwrap()
 }
 
 pub fn read_pw_old() -> String {
+    require_input("old");
     trace!("Reading old password with prompt.");
     rpassword::prompt_password("Your current password: ").unwrap()
 }
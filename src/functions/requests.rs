@@ -1,76 +1,122 @@
-use std::{env, error::Error, fmt::Display, path::Path};
+use std::{env, error::Error, fmt::Display, path::Path, process, sync::OnceLock, time::Duration};
 
+use futures_util::StreamExt;
+use goodmorning_bindings::services::v1::{V1Error, V1Response};
 use log::*;
 use reqwest::{
-    header::{self, HeaderValue, COOKIE, USER_AGENT},
+    header::{
+        self, HeaderMap, HeaderValue, CONTENT_TYPE, COOKIE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED, RETRY_AFTER,
+    },
     multipart::{Form, Part},
-    StatusCode,
+    Body, Certificate, Client, Proxy, RequestBuilder, Response, StatusCode,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::{
     fs,
     io::{AsyncReadExt, AsyncWriteExt},
 };
 
 use crate::{
-    exit_codes::{bad_url, download_failed, file_not_found, sync_failed},
-    functions::get_instance,
-    CREDS, DOWNLOAD_RETRIES, EXPECT, HTTP,
+    exit_codes::{bad_client_config, download_failed, file_not_found, sync_failed},
+    functions::{duration_as_string, get_instance, instance_url, map_redirect_in},
+    structs::{CacheEntry, CacheIndex, Validators},
+    CA_CERTS, CONNECT_TIMEOUT, CREDS, DOWNLOAD_RETRIES, EXPECT, HTTPS_PROXY, MAX_WAIT,
+    READ_TIMEOUT, TRUSTED_CERT_ONLY, WAIT,
 };
 
 const INSECURE_WARN: &str = "This request is sent using the insecure http protocol";
 const SENDING: &str = "Sending request";
 
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// The client all requests are sent with, built from main config on first use.
+pub fn client() -> &'static Client {
+    CLIENT.get_or_init(|| match build_client() {
+        Ok(client) => client,
+        Err(e) => {
+            bad_client_config(&e.to_string());
+            unreachable!()
+        }
+    })
+}
+
+fn build_client() -> Result<Client, Box<dyn Error>> {
+    trace!("Building HTTP client.");
+    let mut builder = Client::builder()
+        .user_agent(format!(
+            "{} {} (git {})",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            env!("GIT_HASH")
+        ))
+        .connect_timeout(Duration::from_secs(*CONNECT_TIMEOUT.get().unwrap()));
+
+    let read_timeout = *READ_TIMEOUT.get().unwrap();
+    if read_timeout != 0 {
+        builder = builder.read_timeout(Duration::from_secs(read_timeout));
+    }
+
+    if let Some(proxy) = HTTPS_PROXY.get().unwrap() {
+        debug!("Using https proxy {proxy}");
+        builder = builder.proxy(Proxy::https(proxy)?);
+    }
+
+    if let Some(path) = TRUSTED_CERT_ONLY.get().unwrap() {
+        debug!(
+            "Trusting only the root certificate at {}, other roots are not trusted.",
+            path.to_string_lossy()
+        );
+        builder = builder
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(Certificate::from_pem(&std::fs::read(path)?)?);
+    } else if let Some(path) = CA_CERTS.get().unwrap() {
+        debug!(
+            "Trusting extra root certificates at {}",
+            path.to_string_lossy()
+        );
+        for cert in Certificate::from_pem_bundle(&std::fs::read(path)?)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
 pub async fn post<R: DeserializeOwned, T: Serialize + Sized>(
     url: &str,
     body: T,
 ) -> Result<R, RequestError> {
-    let url = format!(
-        "{}://{url}",
-        if *HTTP.get().unwrap() {
-            debug!("{INSECURE_WARN}");
-            "http"
-        } else {
-            "https"
-        }
-    );
+    post_with(url, body, *WAIT.get().unwrap()).await
+}
+
+/// Same as `post`, but waits out cooldowns and a full job queue even without `--wait`, for
+/// requests sent many at once such as build targets.
+pub async fn post_waiting<R: DeserializeOwned, T: Serialize + Sized>(
+    url: &str,
+    body: T,
+) -> Result<R, RequestError> {
+    post_with(url, body, true).await
+}
+
+async fn post_with<R: DeserializeOwned, T: Serialize + Sized>(
+    url: &str,
+    body: T,
+    wait: bool,
+) -> Result<R, RequestError> {
+    warn_insecure(url);
     debug!("{SENDING} with POST to {url}");
     debug!("Request body: {}", serde_json::to_string(&body).unwrap());
-    let res = reqwest::Client::new()
-        .post(&url)
-        .header(
-            USER_AGENT,
-            &format!(
-                "{} {} (git {})",
-                env!("CARGO_PKG_NAME"),
-                env!("CARGO_PKG_VERSION"),
-                env!("GIT_HASH")
-            ),
-        )
-        .json(&body)
-        .send()
-        .await;
-    let res = match res {
-        Ok(res) => res,
-        Err(e) => {
-            return Err(RequestError::Send {
-                url: url.to_string(),
-                error: e,
-            });
-        }
-    };
+    let res = send_with(url, wait, || client().post(url).json(&body)).await?;
 
-    debug!(
-        "recieved response with status code `{}`",
-        res.status().to_string()
-    );
     debug!("Response recieved, deserializing");
-    let text = res.text().await.unwrap();
+    let text = res.text(url).await?;
     trace!("Revieved response:\n{text}");
     match serde_json::from_str(&text) {
         Ok(out) => Ok(out),
         Err(e) => Err(RequestError::Deserialize {
-            url,
+            url: url.to_string(),
             error: e,
             content: text,
         }),
@@ -82,73 +128,117 @@ pub async fn get_string(
     html: bool,
     token: bool,
 ) -> Result<(String, StatusCode), RequestError> {
-    if raw_url.starts_with("http://") {
-        debug!("{INSECURE_WARN}");
-    }
-
+    warn_insecure(raw_url);
     debug!("{SENDING} with GET to {raw_url}");
-    let mut builder = reqwest::Client::new().get(raw_url).header(
-        USER_AGENT,
-        &format!(
-            "{} {} (git {})",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION"),
-            env!("GIT_HASH")
-        ),
-    );
+    let build = || {
+        let mut builder = client().get(raw_url);
+
+        if token {
+            builder = builder.header(
+                COOKIE,
+                HeaderValue::from_str(&format!("token={}", unsafe { &CREDS.get().unwrap().token }))
+                    .unwrap(),
+            );
+        }
 
-    if token {
-        builder = builder.header(
-            COOKIE,
-            HeaderValue::from_str(&format!("token={}", unsafe { &CREDS.get().unwrap().token }))
-                .unwrap(),
-        );
-    }
+        if html {
+            builder = builder.header(header::ACCEPT, EXPECT);
+        }
 
-    if html {
-        builder = builder.header(header::ACCEPT, EXPECT);
-    }
+        builder
+    };
 
-    let res = builder.send().await;
-    let res = match res {
+    let res = match send(raw_url, build).await {
         Ok(res) => res,
         Err(e) => {
             error!("Error sending request to `{raw_url}`");
-            return Err(RequestError::Send {
-                url: raw_url.to_string(),
-                error: e,
-            });
+            return Err(e);
         }
     };
-    debug!(
-        "recieved response with status code `{}`",
-        res.status().to_string()
-    );
     debug!("Response recieved, deserializing");
 
-    let status = res.status();
-    let text = res.text().await.unwrap();
+    let status = res.status;
+    let text = res.text(raw_url).await?;
     trace!("Revieved response:\n{text}");
     Ok((text, status))
 }
 
 pub async fn get<R: DeserializeOwned>(url: &str) -> Result<R, RequestError> {
-    let url = format!(
-        "{}://{url}",
-        if *HTTP.get().unwrap() {
-            "http"
-        } else {
-            "https"
+    let text = get_string(url, false, false).await?.0;
+    match serde_json::from_str(&text) {
+        Ok(out) => Ok(out),
+        Err(e) => {
+            error!("Deserialization failed");
+            info!("Server response: \n{}", text);
+            Err(RequestError::Deserialize {
+                url: url.to_string(),
+                error: e,
+                content: text,
+            })
         }
-    );
-    let text = get_string(&url, false, false).await?.0;
+    }
+}
+
+/// Same as `get`, but keeps the response in cache and revalidates it with a conditional request,
+/// so an unchanged response costs a `304` instead of the full body.
+pub async fn get_cached<R: DeserializeOwned>(url: &str) -> Result<R, RequestError> {
+    let cached = CacheIndex::dir()
+        .join("responses")
+        .join(format!("{:x}", Sha256::digest(url)));
+    let entry = if fs::try_exists(&cached).await.unwrap_or(false) {
+        CacheIndex::read(|index| index.get(&cached).cloned())
+    } else {
+        None
+    };
+
+    warn_insecure(url);
+    debug!("{SENDING} with GET to {url}");
+    let res = send(url, || match &entry {
+        Some(entry) => conditional(client().get(url), &entry.validators),
+        None => client().get(url),
+    })
+    .await?;
+
+    let text = if res.status == StatusCode::NOT_MODIFIED {
+        debug!("Response not modified, using cached response.");
+        match fs::read_to_string(&cached).await {
+            Ok(text) => {
+                CacheIndex::update(|index| index.refresh(&cached));
+                text
+            }
+            Err(e) => {
+                debug!("Failed to read cached response: {e}");
+                return get(url).await;
+            }
+        }
+    } else {
+        let validators = validators(&res.headers);
+        let status = res.status;
+        let text = res.text(url).await?;
+
+        if status.is_success() && !validators.is_empty() {
+            match save_response(&cached, &text).await {
+                Ok(()) => CacheIndex::update(|index| {
+                    index.insert(
+                        &cached,
+                        CacheEntry::new(0, text.len() as u64).with_validators(validators),
+                    )
+                }),
+                Err(e) => debug!("Failed to cache response: {e}"),
+            }
+        }
+
+        text
+    };
+    trace!("Revieved response:\n{text}");
+
     match serde_json::from_str(&text) {
         Ok(out) => Ok(out),
         Err(e) => {
             error!("Deserialization failed");
             info!("Server response: \n{}", text);
             Err(RequestError::Deserialize {
-                url,
+                url: url.to_string(),
                 error: e,
                 content: text,
             })
@@ -156,21 +246,207 @@ pub async fn get<R: DeserializeOwned>(url: &str) -> Result<R, RequestError> {
     }
 }
 
+/// A response, JSON bodies and bodies of failed requests are read right away, others are left
+/// to be streamed.
+struct Received {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: ReceivedBody,
+}
+
+enum ReceivedBody {
+    Read(Vec<u8>),
+    Unread(Response),
+}
+
+impl Received {
+    /// The body if it has been read, empty otherwise.
+    fn read_body(&self) -> &[u8] {
+        match &self.body {
+            ReceivedBody::Read(body) => body,
+            ReceivedBody::Unread(_) => &[],
+        }
+    }
+
+    async fn bytes(self, url: &str) -> Result<Vec<u8>, RequestError> {
+        match self.body {
+            ReceivedBody::Read(body) => Ok(body),
+            ReceivedBody::Unread(res) => Ok(res
+                .bytes()
+                .await
+                .map_err(|e| RequestError::Send {
+                    url: url.to_string(),
+                    error: e,
+                })?
+                .to_vec()),
+        }
+    }
+
+    async fn text(self, url: &str) -> Result<String, RequestError> {
+        Ok(String::from_utf8_lossy(&self.bytes(url).await?).to_string())
+    }
+}
+
+/// Whether the response says its body is JSON.
+pub fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Sends the request from `build`, in `--wait` mode the request is rebuilt and sent again for
+/// as long as the server asks to come back later, up to `max-wait` seconds in total.
+async fn send(url: &str, build: impl Fn() -> RequestBuilder) -> Result<Received, RequestError> {
+    send_with(url, *WAIT.get().unwrap(), build).await
+}
+
+/// Same as `send`, with `wait` in place of `--wait`.
+async fn send_with(
+    url: &str,
+    wait: bool,
+    build: impl Fn() -> RequestBuilder,
+) -> Result<Received, RequestError> {
+    let mut waited = 0;
+    let mut backoff = 1;
+
+    loop {
+        let res = build().send().await.map_err(|e| RequestError::Send {
+            url: url.to_string(),
+            error: e,
+        })?;
+        let status = res.status();
+        debug!(
+            "recieved response with status code `{}`",
+            status.to_string()
+        );
+        let headers = res.headers().clone();
+        let body = if is_json(&headers) || !status.is_success() {
+            ReceivedBody::Read(
+                res.bytes()
+                    .await
+                    .map_err(|e| RequestError::Send {
+                        url: url.to_string(),
+                        error: e,
+                    })?
+                    .to_vec(),
+            )
+        } else {
+            ReceivedBody::Unread(res)
+        };
+        let received = Received {
+            status,
+            headers,
+            body,
+        };
+
+        if !wait {
+            return Ok(received);
+        }
+
+        let wait = match wait_for(&received, &mut backoff) {
+            Some(wait) => wait,
+            None => return Ok(received),
+        };
+
+        if waited + wait > *MAX_WAIT.get().unwrap() {
+            debug!("Waited {waited} seconds already, giving up on waiting another {wait}.");
+            return Ok(received);
+        }
+
+        info!(
+            "Server asked to wait, retrying in {}.",
+            duration_as_string(wait)
+        );
+        tokio::time::sleep(Duration::from_secs(wait)).await;
+        waited += wait;
+    }
+}
+
+/// Seconds the server wants us to wait before retrying, `None` if there is no need to retry.
+fn wait_for(received: &Received, backoff: &mut u64) -> Option<u64> {
+    if matches!(
+        received.status,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        // Retry-After can also be a date, use backoff for those
+        return Some(
+            received
+                .headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or_else(|| next_backoff(backoff)),
+        );
+    }
+
+    match serde_json::from_slice(received.read_body()) {
+        Ok(res) => retry_after(&res, backoff),
+        Err(_) => None,
+    }
+}
+
+/// Seconds to wait before sending a request again that was answered with `res`, `None` if the
+/// response is not a cooldown or a full job queue.
+fn retry_after(res: &V1Response, backoff: &mut u64) -> Option<u64> {
+    match res {
+        V1Response::Error {
+            kind: V1Error::Cooldown { remaining },
+        } => Some(*remaining),
+        V1Response::Error {
+            kind: V1Error::QueueFull,
+        } => Some(next_backoff(backoff)),
+        _ => None,
+    }
+}
+
+fn next_backoff(backoff: &mut u64) -> u64 {
+    let wait = *backoff;
+    *backoff *= 2;
+    wait
+}
+
+fn warn_insecure(url: &str) {
+    if url.starts_with("http://") {
+        debug!("{INSECURE_WARN}");
+    }
+}
+
+async fn save_response(path: &Path, text: &str) -> Result<(), std::io::Error> {
+    fs::create_dir_all(path.parent().unwrap()).await?;
+    fs::write(path, text).await
+}
+
+fn conditional(mut builder: RequestBuilder, validators: &Validators) -> RequestBuilder {
+    if let Some(etag) = &validators.etag {
+        builder = builder.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        builder = builder.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    builder
+}
+
+fn validators(headers: &HeaderMap) -> Validators {
+    let get = |name| {
+        headers
+            .get(name)
+            .and_then(|value: &HeaderValue| value.to_str().ok())
+            .map(str::to_string)
+    };
+    Validators {
+        etag: get(ETAG),
+        last_modified: get(LAST_MODIFIED),
+    }
+}
+
 pub async fn upload<R: DeserializeOwned>(url: &str, path: &Path) -> Result<R, RequestError> {
     if !tokio::fs::try_exists(path).await.unwrap() {
         error!("No file at {}", path.to_string_lossy());
         file_not_found(path)
     }
 
-    let url = format!(
-        "{}://{url}",
-        if *HTTP.get().unwrap() {
-            "http"
-        } else {
-            "https"
-        }
-    );
-
+    warn_insecure(url);
     trace!("Starting upload for {} to {url}.", path.to_string_lossy());
 
     trace!("Reading file content.");
@@ -188,45 +464,62 @@ pub async fn upload<R: DeserializeOwned>(url: &str, path: &Path) -> Result<R, Re
         unreachable!()
     }
 
+    trace!("Starting upload");
+
+    let res = send(url, || {
+        let form = Form::new().part(
+            "file",
+            Part::bytes(bytes.clone())
+                .file_name("filename.txt")
+                .mime_str("application/octet-stream")
+                .unwrap(),
+        );
+        client().post(url).multipart(form)
+    })
+    .await?;
+
+    deserialize_upload(url, res.text(url).await?)
+}
+
+/// Uploads a file whose content is produced while it is sent, such as an archive built on the
+/// fly.
+///
+/// The body cannot be sent a second time, so the upload is not retried in `--wait` mode.
+pub async fn upload_stream<R: DeserializeOwned>(
+    url: &str,
+    file_name: &str,
+    body: Body,
+) -> Result<R, RequestError> {
+    warn_insecure(url);
+    trace!("Starting streamed upload of {file_name} to {url}.");
+
     let form = Form::new().part(
         "file",
-        Part::bytes(bytes)
-            .file_name("filename.txt")
+        Part::stream(body)
+            .file_name(file_name.to_string())
             .mime_str("application/octet-stream")
             .unwrap(),
     );
-
-    trace!("Starting upload");
-
-    let res = reqwest::Client::new()
-        .post(&url)
+    let send_error = |e| RequestError::Send {
+        url: url.to_string(),
+        error: e,
+    };
+    let res = client()
+        .post(url)
         .multipart(form)
-        .header(
-            USER_AGENT,
-            &format!(
-                "{} {} (git {})",
-                env!("CARGO_PKG_NAME"),
-                env!("CARGO_PKG_VERSION"),
-                env!("GIT_HASH")
-            ),
-        )
         .send()
         .await
-        .map_err(|e| RequestError::Send {
-            url: url.to_string(),
-            error: e,
-        })?;
-
+        .map_err(send_error)?;
     debug!(
-        "Recieved response status code {}.",
+        "recieved response with status code `{}`",
         res.status().to_string()
     );
+    let text = res.text().await.map_err(send_error)?;
 
-    let text = res.text().await.map_err(|e| RequestError::Send {
-        url: url.to_string(),
-        error: e,
-    })?;
+    deserialize_upload(url, text)
+}
 
+fn deserialize_upload<R: DeserializeOwned>(url: &str, text: String) -> Result<R, RequestError> {
     trace!("Deserializing response.");
 
     match serde_json::from_str(&text) {
@@ -235,7 +528,7 @@ pub async fn upload<R: DeserializeOwned>(url: &str, path: &Path) -> Result<R, Re
             error!("Deserialization failed");
             info!("Server response: \n{}", text);
             Err(RequestError::Deserialize {
-                url,
+                url: url.to_string(),
                 error: e,
                 content: text,
             })
@@ -280,77 +573,139 @@ pub async fn get_url(path: &str) -> String {
 }
 
 pub fn get_url_instance(path: &str, instance: &str) -> String {
-    format!("{instance}{path}")
+    format!("{}{path}", instance_url(instance))
 }
 
-pub fn url_domain(url: &str) -> &str {
-    if let Some(stripped) = url.strip_prefix("http://") {
-        debug!("{INSECURE_WARN}");
-        stripped
-    } else if let Some(stripped) = url.strip_prefix("https://") {
-        stripped
-    } else {
-        bad_url("protocol not specified", url);
-        unreachable!()
+pub async fn download(url: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    download_conditional(url, path, &Validators::default()).await?;
+    Ok(())
+}
+
+/// Downloads the file unless the copy at `path` still matches `validators`,
+/// returns validators of the new copy, or `None` if the existing copy is kept.
+pub async fn download_conditional(
+    url: &str,
+    path: &Path,
+    validators: &Validators,
+) -> Result<Option<Validators>, Box<dyn Error>> {
+    match download_inner(url, path, validators, false).await? {
+        Download::Saved(validators) => Ok(Some(validators)),
+        Download::NotModified => Ok(None),
+        Download::WithinMap { .. } => unreachable!(),
     }
-    .split('/')
-    .next()
-    .unwrap()
 }
 
-pub async fn download_raw(raw_url: &str, path: &Path) -> Result<(), Box<dyn Error>> {
-    trace!(
-        "Downloading file from {raw_url} to {}.",
-        path.to_string_lossy()
-    );
-    let mut file = tokio::fs::OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .create(true)
-        .open(path)
-        .await?;
-
-    let mut err = None;
+/// What became of a file fetched with `download_or_map`.
+pub enum Download {
+    /// The file was saved, with validators of the new copy.
+    Saved(Validators),
+    /// The copy at the path still matches the validators and is kept.
+    NotModified,
+    /// The file is within a map, nothing has been written.
+    WithinMap { redirect: String },
+}
+
+/// Same as `download_conditional`, but a `WithinMap` response is returned instead of being
+/// saved as the file.
+pub async fn download_or_map(
+    url: &str,
+    path: &Path,
+    validators: &Validators,
+) -> Result<Download, Box<dyn Error>> {
+    download_inner(url, path, validators, true).await
+}
+
+async fn download_inner(
+    url: &str,
+    path: &Path,
+    validators: &Validators,
+    maps: bool,
+) -> Result<Download, Box<dyn Error>> {
+    warn_insecure(url);
+    trace!("Downloading file from {url} to {}.", path.to_string_lossy());
+
+    let exists = fs::try_exists(path).await?;
+    let mut err: Option<Box<dyn Error + Send + Sync>> = None;
     for retry in 0..*DOWNLOAD_RETRIES.get().unwrap() {
         trace!("Downloading, retry {}.", retry + 1);
-        let res = reqwest::get(raw_url).await;
-        let res = match res {
+        let res = match send(url, || {
+            if exists {
+                conditional(client().get(url), validators)
+            } else {
+                client().get(url)
+            }
+        })
+        .await
+        {
             Ok(res) => res,
             Err(e) => {
                 debug!("Downloading failed at retry {}: {e}.", retry + 1);
-                err = Some(e);
+                err = Some(e.into());
                 continue;
             }
         };
 
-        let status = res.status();
-        trace!("Request returned statuse code {}", status.to_string());
+        if res.status == StatusCode::NOT_MODIFIED && exists {
+            trace!("File not modified, keeping existing copy.");
+            return Ok(Download::NotModified);
+        }
 
-        let bytes = res.bytes().await?;
-        if !status.is_success() {
-            fs::remove_file(path).await?;
-            download_failed(&path.to_string_lossy(), &String::from_utf8(bytes.to_vec())?);
+        if let Some(redirect) = maps
+            .then(|| map_redirect_in(&res.headers, res.read_body()))
+            .flatten()
+        {
+            trace!("File is within a map at {redirect}.");
+            return Ok(Download::WithinMap { redirect });
         }
 
-        trace!("Saving to file.");
-        file.write_all(&bytes).await?;
-        return Ok(());
+        // the existing copy is only replaced once a new one has been downloaded in full
+        if !res.status.is_success() {
+            download_failed(&path.to_string_lossy(), &res.text(url).await?);
+            unreachable!()
+        }
+
+        let validators = self::validators(&res.headers);
+        match save_download(res, path).await {
+            Ok(()) => return Ok(Download::Saved(validators)),
+            Err(e) => {
+                debug!("Downloading failed at retry {}: {e}.", retry + 1);
+                err = Some(e);
+            }
+        }
     }
 
-    Err(err.unwrap().into())
+    Err(match err {
+        Some(e) => e,
+        None => "no download was attempted, `download-retries` is 0".into(),
+    })
 }
 
-pub async fn download(url: &str, path: &Path) -> Result<(), Box<dyn Error>> {
-    let url = format!(
-        "{}://{url}",
-        if *HTTP.get().unwrap() {
-            debug!("{INSECURE_WARN}");
-            "http"
-        } else {
-            "https"
+/// Streams the body to a temporary file next to `path`, then moves it in place.
+async fn save_download(res: Received, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", process::id()));
+    let temp = path.with_file_name(name);
+
+    trace!("Saving to {}.", temp.to_string_lossy());
+    let saved = async {
+        let mut file = fs::File::create(&temp).await?;
+        match res.body {
+            ReceivedBody::Read(body) => file.write_all(&body).await?,
+            ReceivedBody::Unread(res) => {
+                let mut stream = res.bytes_stream();
+                while let Some(chunk) = stream.next().await {
+                    file.write_all(&chunk?).await?;
+                }
+            }
         }
-    );
+        file.flush().await?;
+        fs::rename(&temp, path).await?;
+        Ok(())
+    }
+    .await;
 
-    // println!("{} {}", url, path.to_string_lossy());
-    download_raw(&url, path).await
+    if saved.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+    saved
 }
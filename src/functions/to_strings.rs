@@ -3,12 +3,15 @@ use std::path::PathBuf;
 
 use chrono::{Datelike, Local, TimeZone, Timelike};
 use goodmorning_bindings::services::v1::{ItemVisibility, V1DirItem, V1Job, V1TexUserPublish};
-use goodmorning_bindings::structs::TexCompileDisplay;
+use goodmorning_bindings::structs::{
+    BlueRenderDisplay, EmailVerificationDisplay, GMServices, ProfileAccount, TexCompileDisplay,
+};
 use goodmorning_bindings::traits::SerdeAny;
+use serde_json::Value;
 
+use crate::functions::*;
 use crate::BASE_PATH;
 use crate::FULLPATH;
-use crate::{functions::*, HTTP};
 
 pub fn diritems_tostring(items: &[V1DirItem]) -> String {
     let longest_size = if items.is_empty() {
@@ -140,7 +143,8 @@ fn job_to_string(job: V1Job) -> String {
     format!("[{}] {}", job.id, task_to_string(job.task))
 }
 
-fn task_to_string(task: Box<dyn SerdeAny>) -> String {
+pub fn task_to_string(task: Box<dyn SerdeAny>) -> String {
+    let value = serde_json::to_value(&task);
     let task_any: Box<dyn Any> = task;
 
     match () {
@@ -148,22 +152,40 @@ fn task_to_string(task: Box<dyn SerdeAny>) -> String {
             "Compiling `{}` with compiler `{:?}`.\n      {:?} -> {:?}",
             res.path, res.compiler, res.from, res.to
         ),
-        _ => "Task cannot be displayed".to_string(),
+        _ if let Some(res) = task_any.downcast_ref::<BlueRenderDisplay>() => format!(
+            "Rendering `{}` with preset `{}`.\n      -> `{}`",
+            res.from, res.preset, res.to
+        ),
+        // tasks added to the server later are shown as whatever they serialise to
+        _ => match value {
+            Ok(value) => value_to_string(&value),
+            Err(_) => "Task cannot be displayed".to_string(),
+        },
+    }
+}
+
+/// Describes what running a trigger does.
+pub fn trigger_to_string(value: Box<dyn SerdeAny>) -> String {
+    let json = serde_json::to_value(&value);
+    let value_any: Box<dyn Any> = value;
+
+    match value_any.downcast_ref::<EmailVerificationDisplay>() {
+        Some(display) => format!(
+            "Verifies the email address `{}` of user `{}`.",
+            display.email, display.username
+        ),
+        // triggers added to the server later are shown as whatever they serialise to
+        None => match json {
+            Ok(json) => value_to_string(&json),
+            Err(_) => "Trigger cannot be displayed".to_string(),
+        },
     }
 }
 
 pub fn publishes_to_string(publishes: &[V1TexUserPublish], instance: &str, userid: i64) -> String {
     publishes
         .iter()
-        .map(|item| {
-            publish_to_string(
-                item,
-                &get_url_instance(
-                    &format!("/api/publish/v1/published-file/id/{userid}/{}", item.id),
-                    instance,
-                ),
-            )
-        })
+        .map(|item| publish_to_string(item, &published_file_url(instance, userid, item.id)))
         .collect::<Vec<_>>()
         .join(&format!("\n{GREY}──────────────────{RESET_COLOUR}\n"))
 }
@@ -195,5 +217,93 @@ pub fn publish_to_string(
         .collect::<String>();
 
     let pad = " ".repeat(id.to_string().len() + 3);
-    format!("[{id}] {title}\n{pad}Description: {desc}\n{pad}Published: {year} {month} {day} {hour}:{min}\n{pad}Format: {ext}\n{pad}Url: {}://{url}", if *HTTP.get().unwrap() {"http"} else {"https"})
+    format!("[{id}] {title}\n{pad}Description: {desc}\n{pad}Published: {year} {month} {day} {hour}:{min}\n{pad}Format: {ext}\n{pad}Url: {url}")
+}
+
+pub fn account_to_string(account: &ProfileAccount) -> String {
+    let email = if account.verified {
+        "verified"
+    } else {
+        "not verified"
+    };
+    let services = account
+        .services
+        .iter()
+        .map(|service| match service {
+            GMServices::Tex => "tex",
+            GMServices::Blue => "blue",
+        })
+        .collect::<Vec<_>>();
+    let services = if services.is_empty() {
+        "none".to_string()
+    } else {
+        services.join(", ")
+    };
+    let created = Local
+        .timestamp_opt(account.created as i64, 0)
+        .unwrap()
+        .format("%y %b %e %H:%M");
+
+    let mut out = format!(
+        "{BLUE}{}{RESET_COLOUR} [{}]\n  Email: {email}\n  Services: {services}\n  Joined: {created}",
+        account.username, account.id
+    );
+    if !account.status.is_empty() {
+        out += &format!("\n  Status: {}", account.status);
+    }
+    out
+}
+
+/// Renders JSON as `key value` lines, nested objects and lists are indented under their key.
+pub fn value_to_string(value: &Value) -> String {
+    let mut out = String::new();
+    value_recurse(value, 0, &mut out);
+    out.trim_end().to_string()
+}
+
+fn value_recurse(value: &Value, depth: usize, out: &mut String) {
+    let pad = "  ".repeat(depth);
+    match value {
+        Value::Object(map) => {
+            let longest = map.keys().map(|key| key.len()).max().unwrap_or_default();
+            for (key, value) in map.iter() {
+                if is_nested(value) {
+                    *out += &format!("{pad}{BLUE}{key}{RESET_COLOUR}\n");
+                    value_recurse(value, depth + 1, out);
+                } else {
+                    *out += &format!(
+                        "{pad}{BLUE}{key:<longest$}{RESET_COLOUR} {}\n",
+                        scalar_to_string(value)
+                    );
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter() {
+                if is_nested(item) {
+                    *out += &format!("{pad}{GREY}-{RESET_COLOUR}\n");
+                    value_recurse(item, depth + 1, out);
+                } else {
+                    *out += &format!("{pad}{GREY}-{RESET_COLOUR} {}\n", scalar_to_string(item));
+                }
+            }
+        }
+        value => *out += &format!("{pad}{}\n", scalar_to_string(value)),
+    }
+}
+
+fn is_nested(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null | Value::Object(_) | Value::Array(_) => "none".to_string(),
+        value => value.to_string(),
+    }
 }
@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use goodmorning_bindings::services::v1::V1Response;
+use log::*;
+use reqwest::{header::HeaderMap, Url};
+
+use super::{get, is_json, GREY, RESET_COLOUR};
+
+/// Most `WithinMap` redirects followed for one request, in case they lead back and forth.
+pub const MAX_MAP_REDIRECTS: usize = 5;
+
+/// Url of a `WithinMap` redirect, which is either a full url or relative to the request.
+pub fn map_redirect_url(url: &str, redirect: &str) -> String {
+    match Url::parse(url).and_then(|url| url.join(redirect)) {
+        Ok(url) => url.to_string(),
+        Err(e) => {
+            debug!("Cannot resolve redirect {redirect} against {url}: {e}");
+            redirect.to_string()
+        }
+    }
+}
+
+/// Follows `WithinMap` responses to the items inside the map, other responses are returned
+/// as they are.
+pub async fn follow_map(url: &str, res: V1Response) -> Result<V1Response, Box<dyn Error>> {
+    let mut url = url.to_string();
+    let mut res = res;
+    for _ in 0..MAX_MAP_REDIRECTS {
+        let redirect = match &res {
+            V1Response::WithinMap { redirect } => redirect,
+            _ => return Ok(res),
+        };
+
+        url = map_redirect_url(&url, redirect);
+        eprintln!("{GREY}Showing items within the map at {redirect}.{RESET_COLOUR}");
+        res = get(&url).await?;
+    }
+
+    debug!("Stopped following map redirects after {MAX_MAP_REDIRECTS}.");
+    Ok(res)
+}
+
+/// `url` with its action segment (`file`, `diritems`, ...) replaced by `action`, to ask
+/// something else about the item a `WithinMap` redirect points to.
+pub fn with_map_action(url: &str, action: &str) -> String {
+    let mut segments = url.split('/').collect::<Vec<_>>();
+    let Some(v1) = segments.iter().position(|segment| *segment == "v1") else {
+        return url.to_string();
+    };
+    // items of other users are under `usercontent`
+    let i = if segments.get(v1 + 1) == Some(&"usercontent") {
+        v1 + 2
+    } else {
+        v1 + 1
+    };
+
+    if i < segments.len() {
+        segments[i] = action;
+    }
+    segments.join("/")
+}
+
+/// Redirect of a file response that is a `WithinMap` response, told apart from a JSON file by
+/// the content type and by the body parsing as one.
+pub fn map_redirect_in(headers: &HeaderMap, body: &[u8]) -> Option<String> {
+    if !is_json(headers) {
+        return None;
+    }
+
+    match serde_json::from_slice(body) {
+        Ok(V1Response::WithinMap { redirect }) => Some(redirect),
+        _ => None,
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use futures_util::stream;
+use log::*;
+use reqwest::Body;
+use tokio::sync::mpsc::{self, Sender};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Size of the chunks a streamed archive is sent in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Writes the content of `dir` into a zip archive, paths in the archive are relative to `dir`.
+///
+/// Files and directories whose name `skip` returns true for are left out. The archive is
+/// written front to back, so `writer` does not have to be seekable.
+pub fn zip_dir<W: Write>(
+    dir: &Path,
+    writer: W,
+    skip: impl Fn(&str) -> bool,
+) -> Result<W, Box<dyn Error>> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip_recurse(dir, "", &mut zip, options, &skip)?;
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Zips `dir` on a blocking thread, the archive is produced as the returned body is read.
+pub fn zip_dir_body(dir: PathBuf, skip: fn(&str) -> bool) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<Vec<u8>, io::Error>>(8);
+
+    tokio::task::spawn_blocking(move || {
+        let writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter(tx.clone()));
+        let res = zip_dir(&dir, writer, skip).and_then(|mut writer| Ok(writer.flush()?));
+        if let Err(e) = res {
+            debug!("Zipping {} failed: {e}", dir.to_string_lossy());
+            tx.blocking_send(Err(io::Error::other(e.to_string()))).ok();
+        }
+    });
+
+    Body::wrap_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
+fn zip_recurse<W: Write + Seek>(
+    dir: &Path,
+    prefix: &str,
+    zip: &mut ZipWriter<W>,
+    options: SimpleFileOptions,
+    skip: &dyn Fn(&str) -> bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let name = format!("{prefix}{file_name}");
+        if skip(&file_name) {
+            trace!("Skipping {name}.");
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            trace!("Adding directory {name} to archive.");
+            zip.add_directory(name.as_str(), options)?;
+            zip_recurse(&entry.path(), &format!("{name}/"), zip, options, skip)?;
+        } else if file_type.is_file() {
+            trace!("Adding file {name} to archive.");
+            zip.start_file(name.as_str(), options)?;
+            io::copy(&mut File::open(entry.path())?, zip)?;
+        } else {
+            debug!("Skipping {name}, it is not a file or directory.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Passes everything written to it on to a channel, for use on a blocking thread.
+struct ChannelWriter(Sender<Result<Vec<u8>, io::Error>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "archive is no longer read"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@ use std::error::Error;
 use brewer::{
     commands::{core::Clean, TopLevel, TopLevelSubcommands},
     functions::init_logger,
+    structs::CacheIndex,
+    AUTO_CLEAN,
 };
 
 #[tokio::main]
@@ -18,17 +20,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
     }
     brewer::load()?;
 
-    if !matches!(args.subcommand, TopLevelSubcommands::Clean(_)) {
-        tokio::task::spawn(async {
-            let _ = Clean::clean_cache().await;
-        });
-    }
-
     trace!("Running command {args:?}");
 
     if args.run().await.is_err() && !args.verbose {
         error!("Command exited unsuccessfully, run with `-v` for verbose debug info.");
     }
 
+    if *AUTO_CLEAN.get().unwrap()
+        && !matches!(
+            args.subcommand,
+            TopLevelSubcommands::Clean(_) | TopLevelSubcommands::Cache(_)
+        )
+    {
+        let _ = Clean::clean_cache().await;
+    }
+    CacheIndex::flush();
+
     Ok(())
 }
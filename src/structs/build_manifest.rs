@@ -0,0 +1,114 @@
+use std::path::{Component, Path};
+
+use log::*;
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::exit_codes::{bad_build_manifest, file_not_found};
+
+/// Targets of `brewer build`, read from `gmbuild.toml` at the root of a repo.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BuildManifest {
+    #[serde(default, rename = "target")]
+    pub targets: Vec<BuildTarget>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BuildTarget {
+    pub name: String,
+    /// Source file, relative to the repo root.
+    pub source: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub compiler: Option<String>,
+    /// Local path to download the compiled file to, relative to the repo root.
+    pub output: Option<String>,
+}
+
+impl BuildManifest {
+    pub const FILE_NAME: &'static str = "gmbuild.toml";
+
+    pub async fn load(root: &Path) -> Self {
+        let path = root.join(Self::FILE_NAME);
+        trace!("Reading {}.", Self::FILE_NAME);
+        let s = match fs::read_to_string(&path).await {
+            Ok(s) => s,
+            Err(_) => {
+                file_not_found(&path);
+                unreachable!()
+            }
+        };
+
+        trace!("Deserializing {}.", Self::FILE_NAME);
+        let manifest: Self = match toml::from_str(&s) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let msg = match e.span() {
+                    Some(span) => format!(
+                        "line {}: {}",
+                        s[..span.start].matches('\n').count() + 1,
+                        e.message()
+                    ),
+                    None => e.message().to_string(),
+                };
+                bad_build_manifest(&msg);
+                unreachable!()
+            }
+        };
+
+        if manifest.targets.is_empty() {
+            bad_build_manifest("no targets are listed");
+        }
+
+        for (i, target) in manifest.targets.iter().enumerate() {
+            if manifest.targets[..i]
+                .iter()
+                .any(|other| other.name == target.name)
+            {
+                bad_build_manifest(&format!("target `{}` is listed twice", target.name));
+            }
+            if let Some(output) = &target.output {
+                if !within_repo(output) {
+                    bad_build_manifest(&format!(
+                        "output `{output}` of target `{}` is outside the repo",
+                        target.name
+                    ));
+                }
+            }
+        }
+
+        manifest
+    }
+
+    /// Outputs of all targets, empty if there is no usable manifest at `root`.
+    ///
+    /// Unlike `load` this never exits, it is read whenever a repo is scanned.
+    pub async fn outputs(root: &Path) -> Vec<String> {
+        let s = match fs::read_to_string(root.join(Self::FILE_NAME)).await {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+
+        match toml::from_str::<Self>(&s) {
+            Ok(manifest) => manifest
+                .targets
+                .into_iter()
+                .filter_map(|target| target.output)
+                .filter(|output| within_repo(output))
+                .collect(),
+            Err(e) => {
+                debug!("Not ignoring build outputs, {}: {e}", Self::FILE_NAME);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Whether `output` is a relative path that stays inside the repo root.
+fn within_repo(output: &str) -> bool {
+    Path::new(output)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, MutexGuard},
+};
+
+use chrono::Utc;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+/// The index as loaded by this command, along with how it looked when it was loaded.
+static LOADED: Mutex<Option<(CacheIndex, CacheIndex)>> = Mutex::new(None);
+
+/// Record of everything brewer keeps in its cache directory.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct CacheIndex {
+    pub entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// When the item was last fetched or revalidated.
+    pub fetched: u64,
+    /// When the item was last used, for LRU eviction.
+    pub accessed: u64,
+    /// Last modified of the remote item.
+    pub last_modified: u64,
+    pub size: u64,
+    /// HTTP validators from when the item was fetched.
+    #[serde(default)]
+    pub validators: Validators,
+}
+
+/// Validators for making conditional requests.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+impl CacheEntry {
+    pub fn new(last_modified: u64, size: u64) -> Self {
+        let now = Utc::now().timestamp() as u64;
+        Self {
+            fetched: now,
+            accessed: now,
+            last_modified,
+            size,
+            validators: Validators::default(),
+        }
+    }
+
+    pub fn with_validators(mut self, validators: Validators) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn age(&self) -> u64 {
+        (Utc::now().timestamp() as u64).saturating_sub(self.fetched)
+    }
+}
+
+impl CacheIndex {
+    pub fn dir() -> PathBuf {
+        dirs::cache_dir().unwrap().join(env!("CARGO_PKG_NAME"))
+    }
+
+    pub fn path() -> PathBuf {
+        Self::dir().join("index.json")
+    }
+
+    pub fn key(path: &Path) -> String {
+        path.strip_prefix(Self::dir())
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let s = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => {
+                trace!("No cache index at {:?}, using empty index.", path);
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_str(&s) {
+            Ok(index) => index,
+            Err(e) => {
+                debug!(
+                    "Cache index at {:?} is corrupted, using empty index: {e}",
+                    path
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the index to a temporary file and moves it in place, so the index on disk is
+    /// never half written.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let path = Self::path();
+        let temp = path.with_extension(format!("json.{}.tmp", process::id()));
+        fs::create_dir_all(Self::dir())?;
+        trace!("Saving cache index to {:?}", path);
+        fs::write(&temp, serde_json::to_string(self).unwrap())?;
+        if let Err(e) = fs::rename(&temp, &path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// The index of this command, loaded from disk on first use.
+    fn loaded() -> MutexGuard<'static, Option<(CacheIndex, CacheIndex)>> {
+        let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+        if loaded.is_none() {
+            let index = Self::load();
+            *loaded = Some((index.clone(), index));
+        }
+        loaded
+    }
+
+    /// Reads the index of this command.
+    pub fn read<T>(f: impl FnOnce(&Self) -> T) -> T {
+        f(&Self::loaded().as_ref().unwrap().0)
+    }
+
+    /// Applies `f` to the index of this command, changes are kept until `flush`.
+    pub fn update<T>(f: impl FnOnce(&mut Self) -> T) -> T {
+        f(&mut Self::loaded().as_mut().unwrap().0)
+    }
+
+    /// Saves changes made by this command, on top of whatever other commands have saved since
+    /// the index was loaded.
+    pub fn flush() {
+        let (index, original) = match LOADED.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            Some(loaded) => loaded,
+            None => return,
+        };
+        if index == original {
+            return;
+        }
+
+        let mut merged = Self::load();
+        for key in original.entries.keys() {
+            if !index.entries.contains_key(key) {
+                merged.entries.remove(key);
+            }
+        }
+        for (key, entry) in index.entries {
+            if original.entries.get(&key) != Some(&entry) {
+                merged.entries.insert(key, entry);
+            }
+        }
+
+        if let Err(e) = merged.save() {
+            debug!("Failed to save cache index: {e}");
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&CacheEntry> {
+        self.entries.get(&Self::key(path))
+    }
+
+    pub fn insert(&mut self, path: &Path, entry: CacheEntry) {
+        self.entries.insert(Self::key(path), entry);
+    }
+
+    /// Marks an entry as fetched just now, after it has been revalidated.
+    pub fn refresh(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.get_mut(&Self::key(path)) {
+            let now = Utc::now().timestamp() as u64;
+            entry.fetched = now;
+            entry.accessed = now;
+        }
+    }
+
+    pub fn touch(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.get_mut(&Self::key(path)) {
+            entry.accessed = Utc::now().timestamp() as u64
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    /// Deletes an entry and its file, returns the size freed.
+    pub fn remove(&mut self, key: &str) -> u64 {
+        let entry = match self.entries.remove(key) {
+            Some(entry) => entry,
+            None => return 0,
+        };
+
+        let path = Self::dir().join(key);
+        trace!("Evicting `{}` from cache.", path.to_string_lossy());
+        if let Err(e) = fs::remove_file(&path) {
+            debug!("Failed to delete `{}`: {e}", path.to_string_lossy());
+        }
+        entry.size
+    }
+
+    /// Removes entries matching `f`, returns (count, size) removed.
+    pub fn remove_where(&mut self, f: impl Fn(&str, &CacheEntry) -> bool) -> (u64, u64) {
+        let keys = self
+            .entries
+            .iter()
+            .filter(|(key, entry)| f(key, entry))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        let mut size = 0;
+        for key in keys.iter() {
+            size += self.remove(key);
+        }
+
+        (keys.len() as u64, size)
+    }
+
+    /// Drops entries whose file has gone missing.
+    pub fn prune(&mut self) {
+        let dir = Self::dir();
+        self.entries.retain(|key, _| dir.join(key).exists());
+    }
+
+    /// Evicts least recently used entries until the cache is no larger than `max_size`,
+    /// returns (count, size) removed.
+    pub fn evict_to(&mut self, max_size: u64) -> (u64, u64) {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.accessed))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(_, accessed)| *accessed);
+
+        let mut total = self.total();
+        let mut count = 0;
+        let mut size = 0;
+        for (key, _) in entries {
+            if total <= max_size {
+                break;
+            }
+
+            let freed = self.remove(&key);
+            total -= freed;
+            size += freed;
+            count += 1;
+        }
+
+        (count, size)
+    }
+}
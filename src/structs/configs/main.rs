@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use config_macro_derive::Config;
 use serde::{Deserialize, Serialize};
 use serde_default::DefaultFromSerde;
@@ -10,12 +12,45 @@ pub struct MainConfig {
     #[serde_inline_default(1)]
     #[serde(rename = "download-retries")]
     pub download_retries: u16,
-    /// Time before cached item is considered stale and a refetch is needed.
+    /// Seconds to wait for a connection to an instance.
+    #[serde_inline_default(10)]
+    #[serde(rename = "connect-timeout")]
+    pub connect_timeout: u64,
+    /// Seconds to wait for more data from a response before giving up, 0 to wait forever.
+    #[serde_inline_default(60)]
+    #[serde(rename = "read-timeout")]
+    pub read_timeout: u64,
+    /// Total seconds to spend waiting on cooldowns and rate limits with `--wait` before giving up.
+    #[serde_inline_default(600)]
+    #[serde(rename = "max-wait")]
+    pub max_wait: u64,
+    /// Proxy for https traffic, overrides the `HTTPS_PROXY` environment variable.
+    #[serde_inline_default(None)]
+    #[serde(rename = "https-proxy")]
+    pub https_proxy: Option<String>,
+    /// PEM file of extra root certificates to trust, for instances behind a private CA.
+    #[serde_inline_default(None)]
+    #[serde(rename = "ca-certs")]
+    pub ca_certs: Option<PathBuf>,
+    /// PEM file of the only certificate trusted as a root, in place of the system roots and
+    /// `ca-certs`. This is not pinning, any certificate issued under it is accepted.
+    #[serde_inline_default(None)]
+    #[serde(rename = "trusted-cert-only")]
+    pub trusted_cert_only: Option<PathBuf>,
+    /// Time before cached item is considered stale and needs to be revalidated.
     #[serde_inline_default(3600)]
     #[serde(rename = "max-age")]
     pub max_age: u64,
+    /// Largest the cache can grow to in bytes before least recently used items are evicted.
+    #[serde_inline_default(1073741824)]
+    #[serde(rename = "max-cache-size")]
+    pub max_cache_size: u64,
     /// Automatically runs clean when running commands.
     #[serde_inline_default(true)]
     #[serde(rename = "auto-clean")]
     pub auto_clean: bool,
+    /// Keep a copy of downloaded files so cloning the same files again is free.
+    #[serde_inline_default(true)]
+    #[serde(rename = "object-cache")]
+    pub object_cache: bool,
 }
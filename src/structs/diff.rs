@@ -11,7 +11,7 @@ use std::{
 
 use chrono::Utc;
 use goodmorning_bindings::services::v1::{
-    V1DirTreeItem, V1DirTreeNode, V1Error, V1MulpiplePaths, V1Response,
+    V1DirTreeItem, V1DirTreeNode, V1Error, V1MulpiplePaths, V1Response, V1SelfFromTo,
 };
 use log::*;
 
@@ -26,6 +26,8 @@ use crate::{
     CREDS, OUTPUT_DIR,
 };
 
+use super::{ObjectStore, StoreObject};
+
 const DIR_SIZE: u64 = 0;
 
 #[derive(Debug, PartialEq, Eq, Default)]
@@ -34,6 +36,10 @@ pub struct TreeDiff {
     pub created_dirs: Vec<TreeDiffItem>,
     pub changed: Vec<TreeDiffItem>,
     pub deleted: Vec<TreeDiffItem>,
+    /// Paths that changed between file and directory, the old item is deleted before the new
+    /// item (listed in `created` or `created_dirs`) is created.
+    pub type_changed: Vec<TreeDiffItem>,
+    pub moved: Vec<TreeDiffMove>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -42,22 +48,31 @@ pub struct TreeDiffItem {
     pub path: String,
 }
 
-#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for TreeDiffItem {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(match self.path.len().partial_cmp(&other.path.len()) {
-            Some(cmp) => cmp,
-            None => self.path.cmp(&other.path),
-        })
+        Some(self.cmp(other))
     }
 }
 
+/// Shorter paths first so parents come before their children, ties broken by the path itself.
 impl Ord for TreeDiffItem {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
+        self.path
+            .len()
+            .cmp(&other.path.len())
+            .then_with(|| self.path.cmp(&other.path))
+            .then_with(|| self.size.cmp(&other.size))
     }
 }
 
+/// A deleted item and a created item that turned out to be the same object.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TreeDiffMove {
+    pub size: u64,
+    pub from: String,
+    pub to: String,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct DiffConflicts {
     pub conflicts: Vec<DiffConflictItem>,
@@ -109,6 +124,8 @@ pub enum DiffConflictAction {
     CreateDir,
     Change,
     Delete,
+    Move,
+    TypeChange,
 }
 
 impl TreeDiffItem {
@@ -208,51 +225,142 @@ fn tree_create(tree: &mut V1DirTreeNode, path: &[String]) {
     }
 }
 
+fn tree_take(tree: &mut V1DirTreeNode, path: &[String]) -> Option<V1DirTreeNode> {
+    if path.is_empty() {
+        return None;
+    }
+
+    match &mut tree.content {
+        V1DirTreeItem::Dir { content } if path.len() == 1 => {
+            let index = content.iter().position(|item| item.name == path[0])?;
+            Some(content.remove(index))
+        }
+        V1DirTreeItem::Dir { content } => {
+            let item = content.iter_mut().find(|item| item.name == path[0])?;
+            tree_take(item, &path[1..])
+        }
+        _ => None,
+    }
+}
+
+fn tree_insert(tree: &mut V1DirTreeNode, path: &[String], mut node: V1DirTreeNode) {
+    if path.is_empty() {
+        return;
+    }
+
+    match &mut tree.content {
+        V1DirTreeItem::Dir { content } if path.len() == 1 => {
+            node.name = path[0].clone();
+            content.retain(|item| item.name != path[0]);
+            content.push(node)
+        }
+        V1DirTreeItem::Dir { content } => {
+            if let Some(item) = content.iter_mut().find(|item| item.name == path[0]) {
+                tree_insert(item, &path[1..], node)
+            }
+        }
+        _ => {}
+    }
+}
+
+fn tree_find<'a>(tree: &'a V1DirTreeNode, path: &[String]) -> Option<&'a V1DirTreeNode> {
+    if path.is_empty() {
+        return Some(tree);
+    }
+
+    match &tree.content {
+        V1DirTreeItem::Dir { content } => {
+            let item = content.iter().find(|item| item.name == path[0])?;
+            tree_find(item, &path[1..])
+        }
+        _ => None,
+    }
+}
+
+fn components(path: &str) -> Vec<String> {
+    PathBuf::from(path)
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+// every file under a directory node as (relative path, item)
+fn tree_files<'a>(
+    node: &'a V1DirTreeNode,
+    current: &Path,
+    out: &mut Vec<(String, &'a V1DirTreeItem)>,
+) {
+    if let V1DirTreeItem::Dir { content } = &node.content {
+        for item in content.iter() {
+            let path = current.join(&item.name);
+            match &item.content {
+                V1DirTreeItem::Dir { .. } => tree_files(item, &path, out),
+                file => out.push((path.to_string_lossy().to_string(), file)),
+            }
+        }
+    }
+}
+
+// every directory under a directory node as relative paths
+fn tree_dirs(node: &V1DirTreeNode, current: &Path, out: &mut Vec<String>) {
+    if let V1DirTreeItem::Dir { content } = &node.content {
+        for item in content.iter().filter(|item| is_dir(&item.content)) {
+            let path = current.join(&item.name);
+            out.push(path.to_string_lossy().to_string());
+            tree_dirs(item, &path, out);
+        }
+    }
+}
+
+// `items` must be sorted by `TreeDiffItem`'s ordering
+fn sorted_contains(items: &[TreeDiffItem], path: &str) -> bool {
+    items
+        .binary_search_by(|item| (item.path.len(), item.path.as_str()).cmp(&(path.len(), path)))
+        .is_ok()
+}
+
+fn is_under(path: &str, parent: &str) -> bool {
+    PathBuf::from(path)
+        .strip_prefix(PathBuf::from(parent))
+        .is_ok()
+}
+
 impl TreeDiff {
     pub fn apply(&self, tree: &mut V1DirTreeNode) {
-        self.deleted.iter().for_each(|diff| {
-            let path = PathBuf::from(&diff.path)
-                .iter()
-                .map(|path| path.to_string_lossy().to_string())
-                .collect::<Vec<_>>();
-            tree_delete(tree, &path)
-        });
-        self.created_dirs.iter().for_each(|diff| {
-            let path = PathBuf::from(&diff.path)
-                .iter()
-                .map(|path| path.to_string_lossy().to_string())
-                .collect::<Vec<_>>();
-            tree_create_dir(tree, &path)
-        });
+        let moved = self
+            .moved
+            .iter()
+            .filter_map(|diff| Some((diff, tree_take(tree, &components(&diff.from))?)))
+            .collect::<Vec<_>>();
+        self.deleted
+            .iter()
+            .chain(self.type_changed.iter())
+            .for_each(|diff| tree_delete(tree, &components(&diff.path)));
+        self.created_dirs
+            .iter()
+            .for_each(|diff| tree_create_dir(tree, &components(&diff.path)));
+        moved
+            .into_iter()
+            .for_each(|(diff, node)| tree_insert(tree, &components(&diff.to), node));
         self.created
             .iter()
             .chain(self.changed.iter())
-            .for_each(|diff| {
-                let path = PathBuf::from(&diff.path)
-                    .iter()
-                    .map(|path| path.to_string_lossy().to_string())
-                    .collect::<Vec<_>>();
-                tree_create(tree, &path)
-            });
+            .for_each(|diff| tree_create(tree, &components(&diff.path)));
     }
+
     pub fn is_empty(&self) -> bool {
         self.created.is_empty()
             && self.created_dirs.is_empty()
             && self.changed.is_empty()
             && self.deleted.is_empty()
+            && self.type_changed.is_empty()
+            && self.moved.is_empty()
     }
 
     pub fn cmp(old: &V1DirTreeNode, new: &V1DirTreeNode) -> Self {
-        let (created, created_dirs, changed) =
-            cmp_updated_recurse(old, new, PathBuf::new().as_path());
-        let deleted = cmp_del_recurse(old, new, PathBuf::new().as_path());
-
-        let mut out = Self {
-            created,
-            created_dirs,
-            changed,
-            deleted,
-        };
+        let mut out = Self::default();
+        cmp_updated_recurse(old, new, PathBuf::new().as_path(), &mut out);
+        cmp_del_recurse(old, new, PathBuf::new().as_path(), &mut out);
         out.sort();
         out
     }
@@ -262,11 +370,192 @@ impl TreeDiff {
         self.created_dirs.sort();
         self.changed.sort();
         self.deleted.sort();
+        self.type_changed.sort();
+        self.moved.sort_by(|this, other| this.from.cmp(&other.from));
+    }
+
+    /// Pairs up deleted and created items that are the same object at a different path.
+    ///
+    /// `same` is called on two files of equal size and decides if their content matches.
+    /// A deleted directory pairs with a created directory of identical layout if at least one
+    /// file in it matches, files that do not match are moved along and then marked as changed.
+    pub fn detect_moves(
+        &mut self,
+        old: &V1DirTreeNode,
+        new: &V1DirTreeNode,
+        same: impl Fn(&str, &V1DirTreeItem, &str, &V1DirTreeItem) -> bool,
+    ) {
+        let same_file =
+            |from: &str, old_item: &V1DirTreeItem, to: &str, new_item: &V1DirTreeItem| {
+                !is_dir(old_item)
+                    && !is_dir(new_item)
+                    && file_meta(old_item).1 == file_meta(new_item).1
+                    && same(from, old_item, to, new_item)
+            };
+
+        let mut deleted = Vec::with_capacity(self.deleted.len());
+        for item in std::mem::take(&mut self.deleted) {
+            let old_node = match tree_find(old, &components(&item.path)) {
+                Some(node) if is_dir(&node.content) => node,
+                _ => {
+                    deleted.push(item);
+                    continue;
+                }
+            };
+
+            let mut old_files = Vec::new();
+            tree_files(old_node, Path::new(""), &mut old_files);
+            let mut old_dirs = Vec::new();
+            tree_dirs(old_node, Path::new(""), &mut old_dirs);
+            old_files.sort_by(|this, other| this.0.cmp(&other.0));
+            old_dirs.sort();
+
+            let found = self.created_dirs.iter().find_map(|created_dir| {
+                if self.created_dirs.iter().any(|other| {
+                    other.path != created_dir.path && is_under(&created_dir.path, &other.path)
+                }) {
+                    return None;
+                }
+
+                let new_node = tree_find(new, &components(&created_dir.path))?;
+                let mut new_files = Vec::new();
+                tree_files(new_node, Path::new(""), &mut new_files);
+                let mut new_dirs = Vec::new();
+                tree_dirs(new_node, Path::new(""), &mut new_dirs);
+                new_files.sort_by(|this, other| this.0.cmp(&other.0));
+                new_dirs.sort();
+
+                if old_dirs != new_dirs
+                    || old_files.len() != new_files.len()
+                    || old_files
+                        .iter()
+                        .zip(new_files.iter())
+                        .any(|(this, other)| this.0 != other.0)
+                {
+                    return None;
+                }
+
+                let unmatched = old_files
+                    .iter()
+                    .zip(new_files.iter())
+                    .filter(|((rel, old_item), (_, new_item))| {
+                        !same_file(
+                            &PathBuf::from(&item.path).join(rel).to_string_lossy(),
+                            old_item,
+                            &PathBuf::from(&created_dir.path).join(rel).to_string_lossy(),
+                            new_item,
+                        )
+                    })
+                    .map(|(_, (rel, new_item))| (rel.clone(), file_meta(new_item).1))
+                    .collect::<Vec<_>>();
+
+                if unmatched.len() == new_files.len() {
+                    return None;
+                }
+
+                Some((created_dir.path.clone(), unmatched))
+            });
+
+            let (to, unmatched) = match found {
+                Some(found) => found,
+                None => {
+                    deleted.push(item);
+                    continue;
+                }
+            };
+
+            trace!("Detected directory {} moved to {to}.", item.path);
+            self.created_dirs
+                .retain(|created_dir| !is_under(&created_dir.path, &to));
+            self.created.retain(|created| !is_under(&created.path, &to));
+            for (rel, size) in unmatched {
+                self.changed
+                    .push(TreeDiffItem::from(PathBuf::from(&to).join(rel), size));
+            }
+            self.moved.push(TreeDiffMove {
+                size: DIR_SIZE,
+                from: item.path,
+                to,
+            });
+        }
+        self.deleted = deleted;
+
+        let mut deleted = Vec::with_capacity(self.deleted.len());
+        for item in std::mem::take(&mut self.deleted) {
+            let old_item = match tree_find(old, &components(&item.path)) {
+                Some(node) if !is_dir(&node.content) => &node.content,
+                _ => {
+                    deleted.push(item);
+                    continue;
+                }
+            };
+
+            let index = self.created.iter().position(|created| {
+                tree_find(new, &components(&created.path)).is_some_and(|node| {
+                    same_file(&item.path, old_item, &created.path, &node.content)
+                })
+            });
+
+            match index {
+                Some(index) => {
+                    let created = self.created.remove(index);
+                    trace!("Detected file {} moved to {}.", item.path, created.path);
+                    self.moved.push(TreeDiffMove {
+                        size: item.size,
+                        from: item.path,
+                        to: created.path,
+                    })
+                }
+                None => deleted.push(item),
+            }
+        }
+        self.deleted = deleted;
+
+        self.sort();
+    }
+
+    /// Pairs up remote moves, the server keeps `last_modified` when an item is moved.
+    ///
+    /// Files only count as the same object if no other deleted or created file has the same
+    /// size and `last_modified`, otherwise there is no telling which one was moved.
+    pub fn detect_remote_moves(&mut self, old: &V1DirTreeNode, new: &V1DirTreeNode) {
+        let mut gone = Vec::new();
+        for item in self.deleted.iter().chain(self.type_changed.iter()) {
+            match tree_find(old, &components(&item.path)) {
+                Some(node) if is_dir(&node.content) => {
+                    tree_files(node, Path::new(&item.path), &mut gone)
+                }
+                Some(node) => gone.push((item.path.clone(), &node.content)),
+                None => {}
+            }
+        }
+        let gone = gone
+            .into_iter()
+            .map(|(_, item)| file_meta(item))
+            .collect::<Vec<_>>();
+        let appeared = self
+            .created
+            .iter()
+            .filter_map(|item| tree_find(new, &components(&item.path)))
+            .filter(|node| !is_dir(&node.content))
+            .map(|node| file_meta(&node.content))
+            .collect::<Vec<_>>();
+
+        let unique = |meta: (u64, u64)| {
+            gone.iter().filter(|other| **other == meta).count() <= 1
+                && appeared.iter().filter(|other| **other == meta).count() <= 1
+        };
+
+        self.detect_moves(old, new, |_, old_item, _, new_item| {
+            let meta = file_meta(old_item);
+            meta == file_meta(new_item) && unique(meta)
+        });
     }
 
     pub fn conflict(&self, remote: &TreeDiff) -> DiffConflicts {
         let mut out = Vec::new();
-        for created in self.created.iter() {
+        let retyped = |path: &str| self.type_changed.iter().any(|item| item.path == path);
+        for created in self.created.iter().filter(|item| !retyped(&item.path)) {
             if let Some(other) = remote.path_modified(&created.path) {
                 out.push(DiffConflictItem {
                     path: created.path.to_string(),
@@ -275,7 +564,7 @@ impl TreeDiff {
                 })
             }
         }
-        for created_dir in self.created_dirs.iter() {
+        for created_dir in self.created_dirs.iter().filter(|item| !retyped(&item.path)) {
             if let Some(other) = remote.path_modified(&created_dir.path) {
                 out.push(DiffConflictItem {
                     path: created_dir.path.to_string(),
@@ -302,6 +591,31 @@ impl TreeDiff {
                 })
             }
         }
+        for type_changed in self.type_changed.iter() {
+            if let Some(other) = remote
+                .folder_modified(&type_changed.path)
+                .or_else(|| remote.path_modified(&type_changed.path))
+            {
+                out.push(DiffConflictItem {
+                    path: type_changed.path.to_string(),
+                    fs: DiffConflictAction::TypeChange,
+                    remote: other,
+                })
+            }
+        }
+        for moved in self.moved.iter() {
+            if let Some(other) = remote
+                .folder_modified(&moved.from)
+                .or_else(|| remote.path_modified(&moved.from))
+                .or_else(|| remote.path_modified(&moved.to))
+            {
+                out.push(DiffConflictItem {
+                    path: moved.from.to_string(),
+                    fs: DiffConflictAction::Move,
+                    remote: other,
+                })
+            }
+        }
 
         DiffConflicts { conflicts: out }
     }
@@ -315,24 +629,26 @@ impl TreeDiff {
             return Some(DiffConflictAction::Delete);
         }
         if self
-            .created
-            .binary_search_by_key(&path, |item| &item.path)
-            .is_ok()
+            .type_changed
+            .iter()
+            .any(|type_changed| is_under(path, &type_changed.path))
         {
-            return Some(DiffConflictAction::Create);
+            return Some(DiffConflictAction::TypeChange);
         }
         if self
-            .created_dirs
-            .binary_search_by_key(&path, |item| &item.path)
-            .is_ok()
+            .moved
+            .iter()
+            .any(|moved| is_under(path, &moved.from) || moved.to == path)
         {
+            return Some(DiffConflictAction::Move);
+        }
+        if sorted_contains(&self.created, path) {
+            return Some(DiffConflictAction::Create);
+        }
+        if sorted_contains(&self.created_dirs, path) {
             return Some(DiffConflictAction::CreateDir);
         }
-        if self
-            .changed
-            .binary_search_by_key(&path, |item| &item.path)
-            .is_ok()
-        {
+        if sorted_contains(&self.changed, path) {
             return Some(DiffConflictAction::Change);
         }
 
@@ -340,6 +656,13 @@ impl TreeDiff {
     }
 
     pub fn folder_modified(&self, path: &str) -> Option<DiffConflictAction> {
+        if self
+            .type_changed
+            .iter()
+            .any(|type_changed| is_under(&type_changed.path, path))
+        {
+            return Some(DiffConflictAction::TypeChange);
+        }
         if self.created.iter().any(|created| {
             PathBuf::from(&created.path)
                 .strip_prefix(PathBuf::from(&path))
@@ -361,6 +684,13 @@ impl TreeDiff {
         }) {
             return Some(DiffConflictAction::Change);
         }
+        if self
+            .moved
+            .iter()
+            .any(|moved| is_under(&moved.from, path) || is_under(&moved.to, path))
+        {
+            return Some(DiffConflictAction::Move);
+        }
 
         None
     }
@@ -370,70 +700,28 @@ impl TreeDiff {
         head: &FsHead,
         instance: &str,
         owned: bool,
+        remote: &V1DirTreeNode,
     ) -> Result<(), Box<dyn Error>> {
+        if self.type_changed.iter().any(|item| item.path.is_empty()) {
+            return Err("root of the repository changed between file and directory".into());
+        }
+
         let _stdout = io::stdout();
         let output = OUTPUT_DIR.get().unwrap();
 
-        if !self.deleted.is_empty() {
-            let counting = Arc::new(AtomicU32::new(0));
-            let total = self.deleted.len() as u32;
-
-            print!("\rDeleting objects (0/{total})... 0%");
-            io::stdout().flush()?;
-
-            async fn delete(
-                path: PathBuf,
-                display_path: &str,
-                counting: Arc<AtomicU32>,
-                total: u32,
-            ) {
-                async fn task(
-                    path: &Path,
-                    display_path: &str,
-                    counting: Arc<AtomicU32>,
-                    total: u32,
-                ) -> Result<(), Box<dyn Error>> {
-                    if !tokio::fs::try_exists(&path).await? {
-                        trace!("{display_path} does not exist, skipping delete item.");
-                    } else if tokio::fs::metadata(&path).await?.is_dir() {
-                        trace!("Deleting directory {display_path}.");
-                        tokio::fs::remove_dir_all(&path).await?;
-                    } else {
-                        trace!("Deleting file {display_path}.");
-                        tokio::fs::remove_file(&path).await?;
-                    }
-
-                    let counting = counting.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                    print!(
-                        "\rDeleting objects ({counting}/{total})... {}%",
-                        counting * 100 / total
-                    );
-                    io::stdout().flush().unwrap();
-
-                    Ok(())
-                }
-
-                if let Err(e) = task(&path, display_path, counting.clone(), total).await {
-                    fs_error(
-                        &e.to_string(),
-                        &FsAction::new(path, FsActionType::DeleteItem),
-                    )
-                }
-            }
-
-            let mut tasks = Vec::with_capacity(self.deleted.len());
-            for item in self.deleted.iter() {
-                let display_path = item.path.trim_matches('/');
-                let path = output.join(display_path);
-
-                tasks.push(delete(path, display_path, counting.clone(), total));
-            }
-
-            for task in tasks {
-                task.await
-            }
+        // a deleted directory holding a move source has to stay until the move is done
+        let (late_deleted, early_deleted): (Vec<_>, Vec<_>) = self
+            .deleted
+            .iter()
+            .chain(self.type_changed.iter())
+            .partition(|deleted| {
+                self.moved
+                    .iter()
+                    .any(|moved| is_under(&moved.from, &deleted.path))
+            });
 
-            println!("\rDeleting objects ({total}/{total}), done.",);
+        if !early_deleted.is_empty() {
+            pull_delete(output, &early_deleted).await?;
         }
 
         if !self.created_dirs.is_empty() {
@@ -489,6 +777,36 @@ impl TreeDiff {
             println!("\rCreating directories ({total}/{total}), done.",);
         }
 
+        if !self.moved.is_empty() {
+            let total = self.moved.len();
+
+            print!("\rMoving objects (0/{total})... 0%");
+            io::stdout().flush()?;
+
+            for (i, item) in self.moved.iter().enumerate() {
+                let from = output.join(item.from.trim_matches('/'));
+                let to = output.join(item.to.trim_matches('/'));
+
+                trace!("Moving {} to {}.", item.from, item.to);
+                if let Err(e) = tokio::fs::rename(&from, &to).await {
+                    fs_error(&e.to_string(), &FsAction::new(to, FsActionType::MoveItem))
+                }
+
+                print!(
+                    "\rMoving objects ({}/{total})... {}%",
+                    i + 1,
+                    (i + 1) * 100 / total
+                );
+                io::stdout().flush()?;
+            }
+
+            println!("\rMoving objects ({total}/{total}), done.");
+        }
+
+        if !late_deleted.is_empty() {
+            pull_delete(output, &late_deleted).await?;
+        }
+
         if !(self.created.is_empty() && self.changed.is_empty()) {
             let creds = unsafe { CREDS.get().unwrap() };
             let counting = Arc::new(AtomicU64::new(0));
@@ -505,6 +823,7 @@ impl TreeDiff {
                 path: PathBuf,
                 display_path: String,
                 url: String,
+                object: Option<StoreObject>,
                 size: u64,
                 counting: Arc<AtomicU64>,
                 total: u64,
@@ -513,14 +832,27 @@ impl TreeDiff {
                     path: &Path,
                     display_path: String,
                     url: String,
+                    object: Option<StoreObject>,
                     size: u64,
                     counting: Arc<AtomicU64>,
                     total: u64,
                 ) -> Result<(), Box<dyn Error>> {
                     io::stdout().flush().unwrap();
-                    trace!("Downloading item {display_path}.");
-                    if let Err(e) = download(&url, path).await {
-                        download_failed(&display_path, &e.to_string());
+                    let restored = match &object {
+                        Some(object) => ObjectStore::restore(object, path).await,
+                        None => false,
+                    };
+
+                    if !restored {
+                        trace!("Downloading item {display_path}.");
+                        if let Err(e) = download(&url, path).await {
+                            download_failed(&display_path, &e.to_string());
+                        }
+                        if let Some(object) = &object {
+                            if let Err(e) = ObjectStore::save(path, object).await {
+                                debug!("Failed to save {display_path} to object cache: {e}");
+                            }
+                        }
                     }
                     let counting =
                         counting.fetch_add(size, std::sync::atomic::Ordering::Relaxed) + size;
@@ -538,7 +870,16 @@ impl TreeDiff {
                     Ok(())
                 }
 
-                if let Err(e) = task(&path, display_path, url, size, counting.clone(), total).await
+                if let Err(e) = task(
+                    &path,
+                    display_path,
+                    url,
+                    object,
+                    size,
+                    counting.clone(),
+                    total,
+                )
+                .await
                 {
                     fs_error(
                         &e.to_string(),
@@ -566,6 +907,17 @@ impl TreeDiff {
                     )
                 };
 
+                let object = match tree_find(remote, &components(&item.path)) {
+                    Some(node) if !is_dir(&node.content) => ObjectStore::object(
+                        instance,
+                        head.id,
+                        &remote_path,
+                        file_meta(&node.content).0,
+                        item.size,
+                    ),
+                    _ => None,
+                };
+
                 let display_path = item.path.trim_matches('/').to_string();
                 let path = output.join(&display_path);
                 let size = item.size;
@@ -574,6 +926,7 @@ impl TreeDiff {
                     path,
                     display_path,
                     url,
+                    object,
                     size,
                     counting,
                     total,
@@ -595,16 +948,35 @@ impl TreeDiff {
     }
 
     pub async fn push(&self, head: &FsHead) -> Result<(), Box<dyn Error>> {
+        if self.type_changed.iter().any(|item| item.path.is_empty()) {
+            return Err("root of the repository changed between file and directory".into());
+        }
+
         let creds = unsafe { CREDS.get().unwrap() };
 
-        if !self.deleted.is_empty() {
-            let url = get_url("/api/storage/v1/delete-multiple").await;
+        // a deleted directory holding a move source has to stay until the move is done
+        let (late_deleted, early_deleted): (Vec<_>, Vec<_>) = self
+            .deleted
+            .iter()
+            .chain(self.type_changed.iter())
+            .partition(|deleted| {
+                self.moved
+                    .iter()
+                    .any(|moved| is_under(&moved.from, &deleted.path))
+            });
+
+        if !early_deleted.is_empty() {
+            push_delete(head, &early_deleted).await?;
+        }
 
-            print!("\rDeleting objects...");
+        if !self.created_dirs.is_empty() {
+            let url = get_url("/api/storage/v1/mkdir-multiple").await;
+
+            print!("\rCreating directories...");
             io::stdout().flush()?;
 
             let paths = self
-                .deleted
+                .created_dirs
                 .iter()
                 .map(|item| format!("{}/{}", head.path, item.path))
                 .collect::<Vec<_>>();
@@ -627,61 +999,53 @@ impl TreeDiff {
 
             for (path, res) in paths.iter().zip(res.into_iter()) {
                 match res {
-                    V1Response::FileItemDeleted => trace!("Deleted {}", path),
-                    V1Response::Error {
-                        kind: V1Error::FileNotFound,
-                    } => {
-                        debug!("Delete {} returns file not found.", path)
-                    }
+                    V1Response::FileItemCreated => trace!("Created directory {}", path),
                     res => {
                         v1_handle(&res).unwrap();
-                        unexpected_response("FileItemDeleted", res)
+                        unexpected_response("FileItemCreated", res)
                     }
                 }
             }
 
-            println!("\rDeleting objects, done.")
+            println!("\rCreating directories, done.")
         }
 
-        if !self.created_dirs.is_empty() {
-            let url = get_url("/api/storage/v1/mkdir-multiple").await;
+        if !self.moved.is_empty() {
+            let url = get_url("/api/storage/v1/move").await;
+            let total = self.moved.len();
 
-            print!("\rCreating directories...");
+            print!("\rMoving objects (0/{total})... 0%");
             io::stdout().flush()?;
 
-            let paths = self
-                .created_dirs
-                .iter()
-                .map(|item| format!("{}/{}", head.path, item.path))
-                .collect::<Vec<_>>();
-
-            let body = V1MulpiplePaths {
-                token: creds.token.clone(),
-                paths: paths.clone(),
-            };
-
-            let res: V1Response = post(&url, body).await?;
-
-            let res = match res {
-                V1Response::Multi { res } => res,
-                res => {
-                    v1_handle(&res).unwrap();
-                    unexpected_response("Multi", res);
-                    unreachable!()
-                }
-            };
+            for (i, item) in self.moved.iter().enumerate() {
+                let body = V1SelfFromTo {
+                    token: creds.token.clone(),
+                    from: format!("{}/{}", head.path, item.from),
+                    to: format!("{}/{}", head.path, item.to),
+                };
 
-            for (path, res) in paths.iter().zip(res.into_iter()) {
+                let res: V1Response = post(&url, body).await?;
                 match res {
-                    V1Response::FileItemCreated => trace!("Created directory {}", path),
+                    V1Response::Moved => trace!("Moved {} to {}", item.from, item.to),
                     res => {
                         v1_handle(&res).unwrap();
-                        unexpected_response("FileItemCreated", res)
+                        unexpected_response("Moved", res)
                     }
                 }
+
+                print!(
+                    "\rMoving objects ({}/{total})... {}%",
+                    i + 1,
+                    (i + 1) * 100 / total
+                );
+                io::stdout().flush()?;
             }
 
-            println!("\rCreating directories, done.")
+            println!("\rMoving objects ({total}/{total}), done.")
+        }
+
+        if !late_deleted.is_empty() {
+            push_delete(head, &late_deleted).await?;
         }
 
         if !(self.changed.is_empty() && self.created.is_empty()) {
@@ -778,6 +1142,112 @@ impl TreeDiff {
     }
 }
 
+async fn pull_delete(output: &Path, items: &[&TreeDiffItem]) -> Result<(), Box<dyn Error>> {
+    let counting = Arc::new(AtomicU32::new(0));
+    let total = items.len() as u32;
+
+    print!("\rDeleting objects (0/{total})... 0%");
+    io::stdout().flush()?;
+
+    async fn delete(path: PathBuf, display_path: &str, counting: Arc<AtomicU32>, total: u32) {
+        async fn task(
+            path: &Path,
+            display_path: &str,
+            counting: Arc<AtomicU32>,
+            total: u32,
+        ) -> Result<(), Box<dyn Error>> {
+            if !tokio::fs::try_exists(&path).await? {
+                trace!("{display_path} does not exist, skipping delete item.");
+            } else if tokio::fs::metadata(&path).await?.is_dir() {
+                trace!("Deleting directory {display_path}.");
+                tokio::fs::remove_dir_all(&path).await?;
+            } else {
+                trace!("Deleting file {display_path}.");
+                tokio::fs::remove_file(&path).await?;
+            }
+
+            let counting = counting.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            print!(
+                "\rDeleting objects ({counting}/{total})... {}%",
+                counting * 100 / total
+            );
+            io::stdout().flush().unwrap();
+
+            Ok(())
+        }
+
+        if let Err(e) = task(&path, display_path, counting.clone(), total).await {
+            fs_error(
+                &e.to_string(),
+                &FsAction::new(path, FsActionType::DeleteItem),
+            )
+        }
+    }
+
+    let mut tasks = Vec::with_capacity(items.len());
+    for item in items.iter() {
+        let display_path = item.path.trim_matches('/');
+        let path = output.join(display_path);
+
+        tasks.push(delete(path, display_path, counting.clone(), total));
+    }
+
+    for task in tasks {
+        task.await
+    }
+
+    println!("\rDeleting objects ({total}/{total}), done.",);
+
+    Ok(())
+}
+
+async fn push_delete(head: &FsHead, items: &[&TreeDiffItem]) -> Result<(), Box<dyn Error>> {
+    let creds = unsafe { CREDS.get().unwrap() };
+    let url = get_url("/api/storage/v1/delete-multiple").await;
+
+    print!("\rDeleting objects...");
+    io::stdout().flush()?;
+
+    let paths = items
+        .iter()
+        .map(|item| format!("{}/{}", head.path, item.path))
+        .collect::<Vec<_>>();
+
+    let body = V1MulpiplePaths {
+        token: creds.token.clone(),
+        paths: paths.clone(),
+    };
+
+    let res: V1Response = post(&url, body).await?;
+
+    let res = match res {
+        V1Response::Multi { res } => res,
+        res => {
+            v1_handle(&res).unwrap();
+            unexpected_response("Multi", res);
+            unreachable!()
+        }
+    };
+
+    for (path, res) in paths.iter().zip(res.into_iter()) {
+        match res {
+            V1Response::FileItemDeleted => trace!("Deleted {}", path),
+            V1Response::Error {
+                kind: V1Error::FileNotFound,
+            } => {
+                debug!("Delete {} returns file not found.", path)
+            }
+            res => {
+                v1_handle(&res).unwrap();
+                unexpected_response("FileItemDeleted", res)
+            }
+        }
+    }
+
+    println!("\rDeleting objects, done.");
+    Ok(())
+}
+
 fn total(items: &[TreeDiffItem]) -> u64 {
     items.iter().map(|item| item.size).sum()
 }
@@ -809,148 +1279,87 @@ impl From<PathBuf> for TreeDiffItem {
 }
 
 // compare old to new
-fn cmp_del_recurse(old: &V1DirTreeNode, new: &V1DirTreeNode, current: &Path) -> Vec<TreeDiffItem> {
-    let mut items = Vec::new();
-
-    match &old.content {
-        V1DirTreeItem::Dir { content: oc } => match &new.content {
-            V1DirTreeItem::Dir { content: nc } => {
-                for entry in oc.iter() {
-                    let dir = is_dir(&entry.content);
-                    let find = nc.iter().find(|new_entry| new_entry.name == entry.name);
-
-                    let find = match find {
-                        Some(f) => f,
-                        None => {
-                            items.push(TreeDiffItem::from(
-                                current.join(&entry.name),
-                                if dir {
-                                    DIR_SIZE
-                                } else {
-                                    file_meta(&entry.content).1
-                                },
-                            ));
-                            continue;
-                        }
-                    };
-
-                    if dir != is_dir(&find.content) {
-                        items.push(TreeDiffItem::from(
-                            current.join(&entry.name),
-                            if dir {
-                                DIR_SIZE
-                            } else {
-                                file_meta(&entry.content).1
-                            },
-                        ));
-                        continue;
-                    }
-
-                    if dir {
-                        items.append(&mut cmp_del_recurse(
-                            entry,
-                            find,
-                            current.join(&entry.name).as_path(),
-                        ))
-                    }
-                }
-            }
-            V1DirTreeItem::File { size, .. } => {
-                items.push(TreeDiffItem::from(current.to_path_buf(), *size))
+fn cmp_del_recurse(old: &V1DirTreeNode, new: &V1DirTreeNode, current: &Path, out: &mut TreeDiff) {
+    let (oc, nc) = match (&old.content, &new.content) {
+        (V1DirTreeItem::Dir { content: oc }, V1DirTreeItem::Dir { content: nc }) => (oc, nc),
+        // a type change at this level is recorded by `cmp_updated_recurse`
+        _ => return,
+    };
+
+    for entry in oc.iter() {
+        let dir = is_dir(&entry.content);
+        let find = match nc.iter().find(|new_entry| new_entry.name == entry.name) {
+            Some(f) => f,
+            None => {
+                out.deleted.push(TreeDiffItem::from(
+                    current.join(&entry.name),
+                    item_size(&entry.content),
+                ));
+                continue;
             }
-        },
-        V1DirTreeItem::File { .. } => unreachable!(),
-    }
+        };
 
-    items
+        if dir && is_dir(&find.content) {
+            cmp_del_recurse(entry, find, current.join(&entry.name).as_path(), out)
+        }
+    }
 }
 
 // compare new to old
-// (created, created_dirs , changed)
 fn cmp_updated_recurse(
     old: &V1DirTreeNode,
     new: &V1DirTreeNode,
     current: &Path,
-) -> (Vec<TreeDiffItem>, Vec<TreeDiffItem>, Vec<TreeDiffItem>) {
-    let mut created = Vec::new();
-    let mut created_dirs = Vec::new();
-    let mut changed = Vec::new();
-
-    match &new.content {
-        V1DirTreeItem::Dir { content: nc } => match &old.content {
-            V1DirTreeItem::Dir { content: oc } => {
-                for entry in nc.iter() {
-                    let dir = is_dir(&entry.content);
-                    let find = oc.iter().find(|old_entry| old_entry.name == entry.name);
-
-                    let find = match find {
-                        Some(f) => f,
-                        None if dir => {
-                            created_dirs
-                                .push(TreeDiffItem::from(current.join(&entry.name), DIR_SIZE));
-
-                            let (mut sub_created, mut sub_created_dirs, mut sub_changed) =
-                                cmp_updated_recurse(
-                                    &V1DirTreeNode {
-                                        visibility: DEFAULT_VIS,
-                                        name: String::new(),
-                                        content: V1DirTreeItem::Dir {
-                                            content: Vec::new(),
-                                        },
-                                    },
-                                    entry,
-                                    current.join(&entry.name).as_path(),
-                                );
-                            created.append(&mut sub_created);
-                            created_dirs.append(&mut sub_created_dirs);
-                            changed.append(&mut sub_changed);
-                            continue;
-                        }
-                        None => {
-                            created.push(TreeDiffItem::from(
-                                current.join(&entry.name),
-                                file_meta(&entry.content).1,
-                            ));
-                            continue;
-                        }
-                    };
-
-                    if dir != is_dir(&find.content) {
-                        if dir {
-                            created_dirs
-                                .push(TreeDiffItem::from(current.join(&entry.name), DIR_SIZE));
-                        } else {
-                            created.push(TreeDiffItem::from(
-                                current.join(&entry.name),
-                                file_meta(&entry.content).1,
-                            ));
-                        }
-                        continue;
-                    }
-
-                    if dir {
-                        let (mut sub_created, mut sub_created_dirs, mut sub_changed) =
-                            cmp_updated_recurse(find, entry, current.join(&entry.name).as_path());
-                        created.append(&mut sub_created);
-                        created_dirs.append(&mut sub_created_dirs);
-                        changed.append(&mut sub_changed);
-                        continue;
-                    }
-
-                    let (last_modified, size) = file_meta(&entry.content);
-                    if last_modified > file_meta(&find.content).0 {
-                        changed.push(TreeDiffItem::from(current.join(&entry.name), size))
-                    }
+    out: &mut TreeDiff,
+) {
+    match (&old.content, &new.content) {
+        (V1DirTreeItem::Dir { content: oc }, V1DirTreeItem::Dir { content: nc }) => {
+            for entry in nc.iter() {
+                let path = current.join(&entry.name);
+                match oc.iter().find(|old_entry| old_entry.name == entry.name) {
+                    Some(find) => cmp_updated_recurse(find, entry, &path, out),
+                    None => cmp_created_recurse(entry, &path, out),
                 }
             }
-            V1DirTreeItem::File { size, .. } => {
-                created.push(TreeDiffItem::from(current.to_path_buf(), *size))
+        }
+        (V1DirTreeItem::File { last_modified, .. }, V1DirTreeItem::File { .. }) => {
+            let (new_last_modified, size) = file_meta(&new.content);
+            if new_last_modified > *last_modified {
+                out.changed
+                    .push(TreeDiffItem::from(current.to_path_buf(), size))
+            }
+        }
+        _ => {
+            out.type_changed.push(TreeDiffItem::from(
+                current.to_path_buf(),
+                item_size(&old.content),
+            ));
+            cmp_created_recurse(new, current, out)
+        }
+    }
+}
+
+// everything in new is created
+fn cmp_created_recurse(new: &V1DirTreeNode, current: &Path, out: &mut TreeDiff) {
+    match &new.content {
+        V1DirTreeItem::Dir { content } => {
+            out.created_dirs
+                .push(TreeDiffItem::from(current.to_path_buf(), DIR_SIZE));
+            for entry in content.iter() {
+                cmp_created_recurse(entry, current.join(&entry.name).as_path(), out)
             }
-        },
-        V1DirTreeItem::File { .. } => unreachable!(),
+        }
+        V1DirTreeItem::File { size, .. } => out
+            .created
+            .push(TreeDiffItem::from(current.to_path_buf(), *size)),
     }
+}
 
-    (created, created_dirs, changed)
+fn item_size(item: &V1DirTreeItem) -> u64 {
+    match item {
+        V1DirTreeItem::Dir { .. } => DIR_SIZE,
+        V1DirTreeItem::File { size, .. } => *size,
+    }
 }
 
 fn is_dir(item: &V1DirTreeItem) -> bool {
@@ -0,0 +1,257 @@
+use std::fmt::Display;
+
+use goodmorning_bindings::services::v1::{Compiler, FromFormat, ToFormat};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SourceFormat {
+    Markdown,
+    Latex,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TargetFormat {
+    Html,
+    Pdf,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompilerKind {
+    PulldownCmark,
+    Pdflatex,
+}
+
+impl From<SourceFormat> for FromFormat {
+    fn from(val: SourceFormat) -> Self {
+        match val {
+            SourceFormat::Markdown => FromFormat::Markdown,
+            SourceFormat::Latex => FromFormat::Latex,
+        }
+    }
+}
+
+impl From<TargetFormat> for ToFormat {
+    fn from(val: TargetFormat) -> Self {
+        match val {
+            TargetFormat::Html => ToFormat::Html,
+            TargetFormat::Pdf => ToFormat::Pdf,
+        }
+    }
+}
+
+impl From<CompilerKind> for Compiler {
+    fn from(val: CompilerKind) -> Self {
+        match val {
+            CompilerKind::PulldownCmark => Compiler::PulldownCmark,
+            CompilerKind::Pdflatex => Compiler::Pdflatex,
+        }
+    }
+}
+
+pub struct FormatInfo<T: 'static> {
+    pub format: T,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// File extensions the format is inferred from.
+    pub extensions: &'static [&'static str],
+}
+
+pub struct CompilerInfo {
+    pub compiler: CompilerKind,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub from: SourceFormat,
+    pub to: TargetFormat,
+}
+
+pub const FROM_FORMATS: &[FormatInfo<SourceFormat>] = &[
+    FormatInfo {
+        format: SourceFormat::Markdown,
+        name: "markdown",
+        aliases: &["md"],
+        extensions: &["md", "markdown"],
+    },
+    FormatInfo {
+        format: SourceFormat::Latex,
+        name: "latex",
+        aliases: &["tex", "lt"],
+        extensions: &["tex", "latex"],
+    },
+];
+
+pub const TO_FORMATS: &[FormatInfo<TargetFormat>] = &[
+    FormatInfo {
+        format: TargetFormat::Html,
+        name: "html",
+        aliases: &["htm"],
+        extensions: &["html", "htm"],
+    },
+    FormatInfo {
+        format: TargetFormat::Pdf,
+        name: "pdf",
+        aliases: &[],
+        extensions: &["pdf"],
+    },
+];
+
+/// Supported compilers, the first compiler for a pair of formats is the default for it and the
+/// first compiler for a source format decides the default target format.
+pub const COMPILERS: &[CompilerInfo] = &[
+    CompilerInfo {
+        compiler: CompilerKind::PulldownCmark,
+        name: "pulldown-cmark",
+        aliases: &["pulldown cmark", "cmark"],
+        from: SourceFormat::Markdown,
+        to: TargetFormat::Html,
+    },
+    CompilerInfo {
+        compiler: CompilerKind::Pdflatex,
+        name: "pdflatex",
+        aliases: &[],
+        from: SourceFormat::Latex,
+        to: TargetFormat::Pdf,
+    },
+];
+
+fn matches_name(name: &str, aliases: &[&str], value: &str) -> bool {
+    let value = value.to_lowercase();
+    name == value || aliases.contains(&value.as_str())
+}
+
+fn format_by_name<T: Copy + 'static>(formats: &[FormatInfo<T>], name: &str) -> Option<T> {
+    formats
+        .iter()
+        .find(|info| matches_name(info.name, info.aliases, name))
+        .map(|info| info.format)
+}
+
+fn format_name<T: PartialEq + 'static>(formats: &[FormatInfo<T>], format: T) -> &'static str {
+    formats
+        .iter()
+        .find(|info| info.format == format)
+        .unwrap()
+        .name
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+    UnknownFormat(String),
+    UnknownCompiler(String),
+    /// The format was not given and cannot be inferred.
+    MissingFrom,
+    MissingTo,
+    Unsupported {
+        from: SourceFormat,
+        to: TargetFormat,
+        compiler: Option<CompilerKind>,
+    },
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat(format) => f.write_fmt(format_args!("unknown format {format}")),
+            Self::UnknownCompiler(compiler) => {
+                f.write_fmt(format_args!("unknown compiler {compiler}"))
+            }
+            Self::MissingFrom => f.write_str("cannot infer format to compile from"),
+            Self::MissingTo => f.write_str("cannot infer format to compile to"),
+            Self::Unsupported {
+                from,
+                to,
+                compiler: Some(compiler),
+            } => f.write_fmt(format_args!(
+                "{} cannot compile {} to {}",
+                COMPILERS
+                    .iter()
+                    .find(|info| info.compiler == *compiler)
+                    .unwrap()
+                    .name,
+                format_name(FROM_FORMATS, *from),
+                format_name(TO_FORMATS, *to)
+            )),
+            Self::Unsupported {
+                from,
+                to,
+                compiler: None,
+            } => f.write_fmt(format_args!(
+                "no compiler can compile {} to {}",
+                format_name(FROM_FORMATS, *from),
+                format_name(TO_FORMATS, *to)
+            )),
+        }
+    }
+}
+
+/// Formats and compiler of a compile request, checked against the supported combinations.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompileTarget {
+    pub from: SourceFormat,
+    pub to: TargetFormat,
+    /// `None` leaves the choice to the server.
+    pub compiler: Option<CompilerKind>,
+}
+
+impl CompileTarget {
+    /// Formats left out are inferred from the extension of `path` and the compiler.
+    pub fn resolve(
+        path: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        compiler: Option<&str>,
+    ) -> Result<Self, FormatError> {
+        let compiler = match compiler {
+            Some(name) => Some(
+                COMPILERS
+                    .iter()
+                    .find(|info| matches_name(info.name, info.aliases, name))
+                    .ok_or_else(|| FormatError::UnknownCompiler(name.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let from = match from {
+            Some(name) => format_by_name(FROM_FORMATS, name)
+                .ok_or_else(|| FormatError::UnknownFormat(name.to_string()))?,
+            None => {
+                let ext = path
+                    .rsplit_once('.')
+                    .map(|(_, ext)| ext.to_lowercase())
+                    .unwrap_or_default();
+                FROM_FORMATS
+                    .iter()
+                    .find(|info| info.extensions.contains(&ext.as_str()))
+                    .map(|info| info.format)
+                    .or(compiler.map(|info| info.from))
+                    .ok_or(FormatError::MissingFrom)?
+            }
+        };
+
+        let to = match to {
+            Some(name) => format_by_name(TO_FORMATS, name)
+                .ok_or_else(|| FormatError::UnknownFormat(name.to_string()))?,
+            None => compiler
+                .or_else(|| COMPILERS.iter().find(|info| info.from == from))
+                .map(|info| info.to)
+                .ok_or(FormatError::MissingTo)?,
+        };
+
+        let supported = |info: &CompilerInfo| info.from == from && info.to == to;
+        match compiler {
+            Some(info) if !supported(info) => Err(FormatError::Unsupported {
+                from,
+                to,
+                compiler: Some(info.compiler),
+            }),
+            None if !COMPILERS.iter().any(supported) => Err(FormatError::Unsupported {
+                from,
+                to,
+                compiler: None,
+            }),
+            compiler => Ok(Self {
+                from,
+                to,
+                compiler: compiler.map(|info| info.compiler),
+            }),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     error::Error,
     path::{Path, PathBuf},
 };
@@ -13,13 +14,14 @@ use tokio::{
 
 use crate::{
     exit_codes::{missing_repo_json, sync_failed},
-    functions::{ignore_tree, DEFAULT_VIS},
+    functions::{hash_files, ignore_tree, instance_url, DEFAULT_VIS},
 };
 
-use super::FsHead;
+use super::{FsHead, TreeDiff};
 
 #[derive(Serialize, Deserialize)]
 pub struct Repo {
+    /// Base url of the instance, such as `https://example.com`.
     pub instance: String,
     pub user: i64,
     pub path: String,
@@ -45,6 +47,7 @@ impl Repo {
             trees: RepoTree {
                 remote: blank.clone(),
                 fs: blank,
+                hashes: HashMap::new(),
             },
         }
     }
@@ -92,9 +95,12 @@ impl Repo {
             .map_err(|e| sync_failed(e.into()))
             .unwrap();
         trace!("Deserializing .gmrepo.json.");
-        serde_json::from_str(&s)
+        let mut repo: Self = serde_json::from_str(&s)
             .map_err(|e| sync_failed(e.into()))
-            .unwrap()
+            .unwrap();
+        // repos cloned before schemes were recorded only have the domain
+        repo.instance = instance_url(&repo.instance);
+        repo
     }
 
     pub async fn find(path: &Path) -> Result<Option<PathBuf>, Box<dyn Error>> {
@@ -125,14 +131,63 @@ impl Repo {
 pub struct RepoTree {
     pub remote: V1DirTreeNode,
     pub fs: V1DirTreeNode,
+    /// Content hashes of files as they were last synced, used to detect moves.
+    #[serde(default)]
+    pub hashes: HashMap<String, String>,
 }
 
 impl RepoTree {
     pub async fn generate(path: &Path, remote: V1DirTreeNode) -> Self {
         trace!("Generating fs repo tree.");
-        Self {
-            remote,
-            fs: ignore_tree(path).await,
+        let fs = ignore_tree(path).await;
+        let blank = V1DirTreeNode {
+            visibility: DEFAULT_VIS,
+            name: String::new(),
+            content: goodmorning_bindings::services::v1::V1DirTreeItem::Dir {
+                content: Vec::new(),
+            },
+        };
+
+        trace!("Hashing synced files.");
+        let files = TreeDiff::cmp(&blank, &fs).created;
+        let hashes = hash_files(path, files.iter().map(|item| item.path.as_str())).await;
+
+        Self { remote, fs, hashes }
+    }
+
+    /// Updates hashes to match the files after `diff` has been synced.
+    pub async fn rehash(&mut self, path: &Path, diff: &TreeDiff) {
+        trace!("Updating hashes of synced files.");
+        for moved in diff.moved.iter() {
+            let keys = self
+                .hashes
+                .keys()
+                .filter(|key| Path::new(key).starts_with(&moved.from))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for key in keys {
+                let hash = self.hashes.remove(&key).unwrap();
+                let rest = Path::new(&key).strip_prefix(&moved.from).unwrap();
+                let key = if rest.as_os_str().is_empty() {
+                    moved.to.clone()
+                } else {
+                    Path::new(&moved.to)
+                        .join(rest)
+                        .to_string_lossy()
+                        .to_string()
+                };
+                self.hashes.insert(key, hash);
+            }
+        }
+
+        for deleted in diff.deleted.iter().chain(diff.type_changed.iter()) {
+            self.hashes
+                .retain(|key, _| !Path::new(key).starts_with(&deleted.path));
         }
+
+        let synced = diff.created.iter().chain(diff.changed.iter());
+        let hashes = hash_files(path, synced.map(|item| item.path.as_str())).await;
+        self.hashes.extend(hashes);
     }
 }
@@ -6,3 +6,13 @@ mod diff;
 pub use diff::*;
 mod gmrepo;
 pub use gmrepo::*;
+mod object_store;
+pub use object_store::*;
+mod cache_index;
+pub use cache_index::*;
+mod publish_mirror;
+pub use publish_mirror::*;
+mod formats;
+pub use formats::*;
+mod build_manifest;
+pub use build_manifest::*;
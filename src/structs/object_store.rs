@@ -0,0 +1,117 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process,
+};
+
+use chrono::Utc;
+use log::*;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::OBJECT_CACHE;
+
+use super::{CacheEntry, CacheIndex};
+
+/// Content addressed store of downloaded files, shared between all repos.
+///
+/// Objects are copied in and out instead of hard linked, so editing a file in a repo can never
+/// change what the store hands out to the next clone.
+pub struct ObjectStore;
+
+/// An object in the store, along with what it is a copy of.
+pub struct StoreObject {
+    pub path: PathBuf,
+    pub last_modified: u64,
+    pub size: u64,
+}
+
+impl ObjectStore {
+    pub fn dir() -> PathBuf {
+        CacheIndex::dir().join("objects")
+    }
+
+    /// Location of the object for a remote file, `None` if the object cache is disabled.
+    pub fn object(
+        instance: &str,
+        user: i64,
+        path: &str,
+        last_modified: u64,
+        size: u64,
+    ) -> Option<StoreObject> {
+        if !*OBJECT_CACHE.get().unwrap() {
+            return None;
+        }
+
+        let key = Self::key(instance, user, path, last_modified, size);
+        Some(StoreObject {
+            path: Self::dir().join(&key[..2]).join(&key[2..]),
+            last_modified,
+            size,
+        })
+    }
+
+    /// Name of the object for a remote file.
+    pub fn key(instance: &str, user: i64, path: &str, last_modified: u64, size: u64) -> String {
+        format!(
+            "{:x}",
+            Sha256::digest(format!(
+                "{instance}\n{user}\n{}\n{last_modified}\n{size}",
+                path.trim_matches('/')
+            ))
+        )
+    }
+
+    /// Copies the object to `path`, returns false if there is no such object.
+    pub async fn restore(object: &StoreObject, path: &Path) -> bool {
+        if !fs::try_exists(&object.path).await.unwrap_or(false) {
+            return false;
+        }
+
+        trace!("Restoring `{}` from object cache.", path.to_string_lossy());
+        if let Err(e) = fs::copy(&object.path, path).await {
+            debug!("Failed to restore from object cache: {e}");
+            return false;
+        }
+
+        // restoring counts as a use, so the object is among the last to be evicted
+        CacheIndex::update(|index| index.touch(&object.path));
+
+        true
+    }
+
+    /// Copies a freshly downloaded file into the store.
+    ///
+    /// The copy is made next to the object and then moved in place, so an object is never seen
+    /// half written.
+    pub async fn save(path: &Path, object: &StoreObject) -> Result<(), Box<dyn Error>> {
+        trace!("Saving `{}` to object cache.", path.to_string_lossy());
+        fs::create_dir_all(object.path.parent().unwrap()).await?;
+        let temp = object.path.with_extension(format!("{}.tmp", process::id()));
+        let res = match fs::copy(path, &temp).await {
+            Ok(_) => fs::rename(&temp, &object.path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = res {
+            let _ = fs::remove_file(&temp).await;
+            return Err(e.into());
+        }
+        CacheIndex::update(|index| {
+            index.insert(
+                &object.path,
+                CacheEntry::new(object.last_modified, object.size),
+            )
+        });
+        Ok(())
+    }
+
+    /// Evicts objects that have not been used for longer than `max_age`, returns
+    /// (count, size) removed.
+    pub fn expire(index: &mut CacheIndex, max_age: u64) -> (u64, u64) {
+        let now = Utc::now().timestamp() as u64;
+        let prefix = CacheIndex::key(&Self::dir());
+        index.remove_where(|key, entry| {
+            Path::new(key).starts_with(&prefix) && now.saturating_sub(entry.accessed) > max_age
+        })
+    }
+}
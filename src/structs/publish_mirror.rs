@@ -0,0 +1,62 @@
+use std::{collections::HashMap, fs, path::Path, process};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use super::Validators;
+
+/// Published items downloaded into a directory by `publishes get --all`.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct PublishMirror {
+    pub items: HashMap<i64, MirroredPublish>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MirroredPublish {
+    /// File name of the item in the mirror directory.
+    pub file: String,
+    pub published: u64,
+    /// HTTP validators from when the item was downloaded.
+    #[serde(default)]
+    pub validators: Validators,
+}
+
+impl PublishMirror {
+    pub const FILE_NAME: &'static str = ".gmpublishes.json";
+
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(Self::FILE_NAME);
+        let s = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => {
+                trace!("No publish mirror index at {:?}, using empty index.", path);
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_str(&s) {
+            Ok(index) => index,
+            Err(e) => {
+                debug!(
+                    "Publish mirror index at {:?} is corrupted, using empty index: {e}",
+                    path
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the index to a temporary file and moves it in place, so an interrupted mirror
+    /// never leaves a half written index.
+    pub fn save(&self, dir: &Path) -> Result<(), std::io::Error> {
+        let path = dir.join(Self::FILE_NAME);
+        let temp = path.with_extension(format!("json.{}.tmp", process::id()));
+        trace!("Saving publish mirror index to {:?}", path);
+        fs::write(&temp, serde_json::to_string(self).unwrap())?;
+        if let Err(e) = fs::rename(&temp, &path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
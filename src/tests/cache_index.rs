@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::structs::{CacheEntry, CacheIndex};
+
+/// Index of three 10 byte entries, `a` being the least recently used.
+fn index() -> CacheIndex {
+    let mut index = CacheIndex::default();
+    for (accessed, key) in ["a", "b", "c"].into_iter().enumerate() {
+        let mut entry = CacheEntry::new(0, 10);
+        entry.accessed = accessed as u64 + 1;
+        index.insert(Path::new(&format!("tests/evict/{key}")), entry);
+    }
+    index
+}
+
+fn keys(index: &CacheIndex) -> Vec<String> {
+    let mut keys = index.entries.keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn evict_least_recently_used() {
+    let mut index = index();
+    assert_eq!(index.evict_to(15), (2, 20));
+    assert_eq!(keys(&index), ["tests/evict/c"]);
+}
+
+#[test]
+fn evict_nothing_under_limit() {
+    let mut index = index();
+    assert_eq!(index.evict_to(30), (0, 0));
+    assert_eq!(index.total(), 30);
+}
+
+#[test]
+fn evict_touched_last() {
+    let mut index = index();
+    index.touch(Path::new("tests/evict/a"));
+    assert_eq!(index.evict_to(20), (1, 10));
+    assert_eq!(keys(&index), ["tests/evict/a", "tests/evict/c"]);
+}
+
+#[test]
+fn stale_entries_are_kept() {
+    let mut index = index();
+    for entry in index.entries.values_mut() {
+        entry.fetched = 0;
+    }
+    assert_eq!(index.evict_to(30), (0, 0));
+    assert_eq!(index.entries.len(), 3);
+}
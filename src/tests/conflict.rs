@@ -1,6 +1,7 @@
 use crate::structs::DiffConflictAction::*;
 use crate::structs::DiffConflictItem;
 use crate::structs::TreeDiff;
+use crate::structs::TreeDiffMove;
 
 #[test]
 fn conflict1() {
@@ -9,12 +10,16 @@ fn conflict1() {
         created_dirs: vec![],
         changed: vec![],
         deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
     };
     let remote = TreeDiff {
         created: vec![],
         created_dirs: vec![],
         changed: vec![],
         deleted: vec!["no".into()],
+        type_changed: vec![],
+        moved: vec![],
     };
 
     assert_eq!(this.conflict(&remote), vec![].into())
@@ -27,12 +32,16 @@ fn conflict2() {
         created_dirs: vec![],
         changed: vec![],
         deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
     };
     let remote = TreeDiff {
         created: vec![],
         created_dirs: vec!["hello".into()],
         changed: vec![],
         deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
     };
 
     assert_eq!(
@@ -53,12 +62,16 @@ fn conflict3() {
         created_dirs: vec![],
         changed: vec![],
         deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
     };
     let remote = TreeDiff {
         created: vec![],
         created_dirs: vec![],
         changed: vec![],
         deleted: vec!["hello".into()],
+        type_changed: vec![],
+        moved: vec![],
     };
 
     assert_eq!(
@@ -79,12 +92,16 @@ fn conflict4() {
         created_dirs: vec![],
         changed: vec![],
         deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
     };
     let this = TreeDiff {
         created: vec![],
         created_dirs: vec![],
         changed: vec![],
         deleted: vec!["hello".into()],
+        type_changed: vec![],
+        moved: vec![],
     };
 
     assert_eq!(
@@ -97,3 +114,67 @@ fn conflict4() {
         .into()
     )
 }
+
+#[test]
+fn conflict5() {
+    let this = TreeDiff {
+        created: vec![],
+        created_dirs: vec![],
+        changed: vec![],
+        deleted: vec![],
+        type_changed: vec![],
+        moved: vec![TreeDiffMove {
+            size: 0,
+            from: "hello".into(),
+            to: "world".into(),
+        }],
+    };
+    let remote = TreeDiff {
+        created: vec![],
+        created_dirs: vec![],
+        changed: vec!["hello/file".into()],
+        deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
+    };
+
+    assert_eq!(
+        this.conflict(&remote),
+        vec![DiffConflictItem {
+            path: "hello".into(),
+            fs: Move,
+            remote: Change
+        }]
+        .into()
+    )
+}
+
+#[test]
+fn conflict6() {
+    let this = TreeDiff {
+        created: vec!["hello".into()],
+        created_dirs: vec![],
+        changed: vec![],
+        deleted: vec![],
+        type_changed: vec!["hello".into()],
+        moved: vec![],
+    };
+    let remote = TreeDiff {
+        created: vec![],
+        created_dirs: vec![],
+        changed: vec!["hello/file".into()],
+        deleted: vec![],
+        type_changed: vec![],
+        moved: vec![],
+    };
+
+    assert_eq!(
+        this.conflict(&remote),
+        vec![DiffConflictItem {
+            path: "hello".into(),
+            fs: TypeChange,
+            remote: Change
+        }]
+        .into()
+    )
+}
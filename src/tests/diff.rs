@@ -1,4 +1,7 @@
-use crate::{functions::DEFAULT_VIS, structs::TreeDiff};
+use crate::{
+    functions::DEFAULT_VIS,
+    structs::{TreeDiff, TreeDiffItem, TreeDiffMove},
+};
 use goodmorning_bindings::services::v1::{V1DirTreeItem, V1DirTreeNode};
 
 #[test]
@@ -337,8 +340,573 @@ fn all_2() {
         TreeDiff {
             created: vec!["test1".into(), "test2/test3".into()],
             created_dirs: vec!["test2".into()],
-            deleted: vec!["test1".into()],
+            deleted: vec![],
             changed: vec!["test4".into()],
+            type_changed: vec!["test1".into()],
+            moved: vec![],
+        }
+    )
+}
+
+#[test]
+fn move_1() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 0,
+                    size: 10,
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![
+                V1DirTreeNode {
+                    name: "test2".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 1,
+                        size: 10,
+                    },
+                },
+                V1DirTreeNode {
+                    name: "test3".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 1,
+                        size: 10,
+                    },
+                },
+            ],
+        },
+    };
+
+    let mut diff = TreeDiff::cmp(&tree1, &tree2);
+    diff.detect_moves(&tree1, &tree2, |from, _, to, _| {
+        from == "test1" && to == "test3"
+    });
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            created: vec![TreeDiffItem {
+                size: 10,
+                path: "test2".into()
+            }],
+            moved: vec![TreeDiffMove {
+                size: 10,
+                from: "test1".into(),
+                to: "test3".into(),
+            }],
+            ..Default::default()
+        }
+    )
+}
+
+#[test]
+fn move_2() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::Dir {
+                    content: vec![
+                        V1DirTreeNode {
+                            name: "test2".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 0,
+                                size: 10,
+                            },
+                        },
+                        V1DirTreeNode {
+                            name: "test3".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 0,
+                                size: 20,
+                            },
+                        },
+                    ],
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test4".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::Dir {
+                    content: vec![
+                        V1DirTreeNode {
+                            name: "test2".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 0,
+                                size: 10,
+                            },
+                        },
+                        V1DirTreeNode {
+                            name: "test3".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 1,
+                                size: 30,
+                            },
+                        },
+                    ],
+                },
+            }],
+        },
+    };
+
+    let mut diff = TreeDiff::cmp(&tree1, &tree2);
+    diff.detect_moves(&tree1, &tree2, |_, _, _, _| true);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            changed: vec![TreeDiffItem {
+                size: 30,
+                path: "test4/test3".into()
+            }],
+            moved: vec![TreeDiffMove {
+                size: 0,
+                from: "test1".into(),
+                to: "test4".into(),
+            }],
+            ..Default::default()
+        }
+    );
+
+    let mut applied = tree1.clone();
+    diff.apply(&mut applied);
+    let names = match &applied.content {
+        V1DirTreeItem::Dir { content } => content
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>(),
+        V1DirTreeItem::File { .. } => unreachable!(),
+    };
+    assert_eq!(names, vec!["test4".to_string()])
+}
+
+#[test]
+fn type_1() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 0,
+                    size: 0,
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::Dir {
+                    content: vec![V1DirTreeNode {
+                        name: "test2".to_string(),
+                        visibility: DEFAULT_VIS,
+                        content: V1DirTreeItem::Dir {
+                            content: vec![V1DirTreeNode {
+                                name: "test3".to_string(),
+                                visibility: DEFAULT_VIS,
+                                content: V1DirTreeItem::File {
+                                    last_modified: 0,
+                                    size: 0,
+                                },
+                            }],
+                        },
+                    }],
+                },
+            }],
+        },
+    };
+
+    let diff = TreeDiff::cmp(&tree1, &tree2);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            created: vec!["test1/test2/test3".into()],
+            created_dirs: vec!["test1".into(), "test1/test2".into()],
+            type_changed: vec!["test1".into()],
+            ..Default::default()
+        }
+    );
+
+    let mut applied = tree1.clone();
+    diff.apply(&mut applied);
+    assert!(TreeDiff::cmp(&applied, &tree2).is_empty())
+}
+
+#[test]
+fn type_2() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::Dir {
+                    content: vec![V1DirTreeNode {
+                        name: "test2".to_string(),
+                        visibility: DEFAULT_VIS,
+                        content: V1DirTreeItem::File {
+                            last_modified: 0,
+                            size: 0,
+                        },
+                    }],
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 0,
+                    size: 0,
+                },
+            }],
+        },
+    };
+
+    let diff = TreeDiff::cmp(&tree1, &tree2);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            created: vec!["test1".into()],
+            type_changed: vec!["test1".into()],
+            ..Default::default()
+        }
+    );
+
+    let mut applied = tree1.clone();
+    diff.apply(&mut applied);
+    assert!(TreeDiff::cmp(&applied, &tree2).is_empty())
+}
+
+#[test]
+fn type_3() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::File {
+            last_modified: 0,
+            size: 0,
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::File {
+            last_modified: 1,
+            size: 0,
+        },
+    };
+
+    assert_eq!(
+        TreeDiff::cmp(&tree1, &tree2),
+        TreeDiff {
+            changed: vec!["".into()],
+            ..Default::default()
+        }
+    );
+    assert!(TreeDiff::cmp(&tree1, &tree1).is_empty())
+}
+
+#[test]
+fn type_4() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 0,
+                    size: 0,
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::File {
+            last_modified: 0,
+            size: 0,
+        },
+    };
+
+    assert_eq!(
+        TreeDiff::cmp(&tree1, &tree2),
+        TreeDiff {
+            created: vec!["".into()],
+            type_changed: vec!["".into()],
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        TreeDiff::cmp(&tree2, &tree1),
+        TreeDiff {
+            created: vec!["test1".into()],
+            created_dirs: vec!["".into()],
+            type_changed: vec!["".into()],
+            ..Default::default()
+        }
+    )
+}
+
+#[test]
+fn remote_move_1() {
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![
+                V1DirTreeNode {
+                    name: "test1".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 10,
+                    },
+                },
+                V1DirTreeNode {
+                    name: "test2".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::Dir {
+                        content: vec![V1DirTreeNode {
+                            name: "test3".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 6,
+                                size: 20,
+                            },
+                        }],
+                    },
+                },
+            ],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![
+                V1DirTreeNode {
+                    name: "test4".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 10,
+                    },
+                },
+                V1DirTreeNode {
+                    name: "test5".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::Dir {
+                        content: vec![V1DirTreeNode {
+                            name: "test3".to_string(),
+                            visibility: DEFAULT_VIS,
+                            content: V1DirTreeItem::File {
+                                last_modified: 6,
+                                size: 20,
+                            },
+                        }],
+                    },
+                },
+            ],
+        },
+    };
+
+    let mut diff = TreeDiff::cmp(&tree1, &tree2);
+    diff.detect_remote_moves(&tree1, &tree2);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            moved: vec![
+                TreeDiffMove {
+                    size: 10,
+                    from: "test1".into(),
+                    to: "test4".into(),
+                },
+                TreeDiffMove {
+                    size: 0,
+                    from: "test2".into(),
+                    to: "test5".into(),
+                },
+            ],
+            ..Default::default()
+        }
+    )
+}
+
+#[test]
+fn remote_move_2() {
+    // two files share size and last modified, either could have been moved
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![
+                V1DirTreeNode {
+                    name: "test1".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 10,
+                    },
+                },
+                V1DirTreeNode {
+                    name: "test2".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 10,
+                    },
+                },
+            ],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test3".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 5,
+                    size: 10,
+                },
+            }],
+        },
+    };
+
+    let mut diff = TreeDiff::cmp(&tree1, &tree2);
+    diff.detect_remote_moves(&tree1, &tree2);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            created: vec![TreeDiffItem {
+                size: 10,
+                path: "test3".into()
+            }],
+            deleted: vec![
+                TreeDiffItem {
+                    size: 10,
+                    path: "test1".into()
+                },
+                TreeDiffItem {
+                    size: 10,
+                    path: "test2".into()
+                },
+            ],
+            ..Default::default()
+        }
+    )
+}
+
+#[test]
+fn remote_move_3() {
+    // same last modified but a different size, a new file uploaded in the same second
+    let tree1 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![V1DirTreeNode {
+                name: "test1".to_string(),
+                visibility: DEFAULT_VIS,
+                content: V1DirTreeItem::File {
+                    last_modified: 5,
+                    size: 10,
+                },
+            }],
+        },
+    };
+    let tree2 = V1DirTreeNode {
+        name: "hello".to_string(),
+        visibility: DEFAULT_VIS,
+        content: V1DirTreeItem::Dir {
+            content: vec![
+                V1DirTreeNode {
+                    name: "test2".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 11,
+                    },
+                },
+                V1DirTreeNode {
+                    name: "test3".to_string(),
+                    visibility: DEFAULT_VIS,
+                    content: V1DirTreeItem::File {
+                        last_modified: 5,
+                        size: 12,
+                    },
+                },
+            ],
+        },
+    };
+
+    let mut diff = TreeDiff::cmp(&tree1, &tree2);
+    diff.detect_remote_moves(&tree1, &tree2);
+
+    assert_eq!(
+        diff,
+        TreeDiff {
+            created: vec![
+                TreeDiffItem {
+                    size: 11,
+                    path: "test2".into()
+                },
+                TreeDiffItem {
+                    size: 12,
+                    path: "test3".into()
+                },
+            ],
+            deleted: vec![TreeDiffItem {
+                size: 10,
+                path: "test1".into()
+            }],
+            ..Default::default()
         }
     )
 }
@@ -0,0 +1,77 @@
+use crate::structs::{CompileTarget, CompilerKind, FormatError, SourceFormat, TargetFormat};
+
+#[test]
+fn infer_from_extension() {
+    assert_eq!(
+        CompileTarget::resolve("notes/doc.md", None, None, None),
+        Ok(CompileTarget {
+            from: SourceFormat::Markdown,
+            to: TargetFormat::Html,
+            compiler: None,
+        })
+    );
+    assert_eq!(
+        CompileTarget::resolve("thesis.TEX", None, None, None),
+        Ok(CompileTarget {
+            from: SourceFormat::Latex,
+            to: TargetFormat::Pdf,
+            compiler: None,
+        })
+    );
+}
+
+#[test]
+fn aliases() {
+    assert_eq!(
+        CompileTarget::resolve("doc.txt", Some("lt"), Some("PDF"), Some("pdflatex")),
+        Ok(CompileTarget {
+            from: SourceFormat::Latex,
+            to: TargetFormat::Pdf,
+            compiler: Some(CompilerKind::Pdflatex),
+        })
+    );
+}
+
+#[test]
+fn infer_from_compiler() {
+    assert_eq!(
+        CompileTarget::resolve("doc", None, None, Some("cmark")),
+        Ok(CompileTarget {
+            from: SourceFormat::Markdown,
+            to: TargetFormat::Html,
+            compiler: Some(CompilerKind::PulldownCmark),
+        })
+    );
+}
+
+#[test]
+fn rejected() {
+    assert_eq!(
+        CompileTarget::resolve("doc", None, None, None),
+        Err(FormatError::MissingFrom)
+    );
+    assert_eq!(
+        CompileTarget::resolve("doc.md", Some("rst"), None, None),
+        Err(FormatError::UnknownFormat("rst".to_string()))
+    );
+    assert_eq!(
+        CompileTarget::resolve("doc.md", None, None, Some("xelatex")),
+        Err(FormatError::UnknownCompiler("xelatex".to_string()))
+    );
+    assert_eq!(
+        CompileTarget::resolve("doc.md", None, Some("pdf"), None),
+        Err(FormatError::Unsupported {
+            from: SourceFormat::Markdown,
+            to: TargetFormat::Pdf,
+            compiler: None,
+        })
+    );
+    assert_eq!(
+        CompileTarget::resolve("doc.tex", None, None, Some("cmark")),
+        Err(FormatError::Unsupported {
+            from: SourceFormat::Latex,
+            to: TargetFormat::Html,
+            compiler: Some(CompilerKind::PulldownCmark),
+        })
+    );
+}
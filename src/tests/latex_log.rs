@@ -0,0 +1,104 @@
+use crate::functions::{parse_latex_log, Diagnostic, Severity};
+
+const LOG: &str = r#"This is pdfTeX, Version 3.141592653-2.6-1.40.25 (TeX Live 2023) (preloaded format=pdflatex 2023.5.1)  1 JAN 2024 12:00
+entering extended mode
+ restricted \write18 enabled.
+ %&-line parsing enabled.
+**main.tex
+(./main.tex
+LaTeX2e <2022-11-01> patch level 1
+L3 programming layer <2023-02-22>
+(/usr/share/texlive/texmf-dist/tex/latex/base/article.cls
+Document Class: article 2022/07/02 v1.4n Standard LaTeX document class
+(/usr/share/texlive/texmf-dist/tex/latex/base/size10.clo
+File: size10.clo 2022/07/02 v1.4n Standard LaTeX file (size option)
+))
+(./chapters/intro.tex
+! Undefined control sequence.
+l.3 \foo
+        (bar)
+The control sequence at the end of the top line
+of your error message was never \def'ed. If you have
+misspelled it (e.g., `\hobx'), type `I' and the correct
+spelling (e.g., `I\hbox'). Otherwise just continue,
+and I'll forget about whatever was undefined.
+
+Overfull \hbox (15.0pt too wide) in paragraph at lines 7--9
+[]\OT1/cmr/m/n/10 A very long line (with parentheses
+ 
+
+)
+Package hyperref Warning: Token not allowed in a PDF string (Unicode):
+(hyperref)                removing `math shift' on input line 12.
+
+LaTeX Warning: Reference `fig:one' on page 1 undefined on input line 20.
+
+[1{/var/lib/texmf/fonts/map/pdftex/updmap/pdftex.map}] (./main.aux) )
+"#;
+
+#[test]
+fn parses_log() {
+    assert_eq!(
+        parse_latex_log(LOG),
+        vec![
+            Diagnostic {
+                file: Some("./chapters/intro.tex".to_string()),
+                line: Some(3),
+                severity: Severity::Error,
+                message: "Undefined control sequence.".to_string(),
+            },
+            Diagnostic {
+                file: Some("./chapters/intro.tex".to_string()),
+                line: Some(7),
+                severity: Severity::Badbox,
+                message: "Overfull \\hbox (15.0pt too wide)".to_string(),
+            },
+            Diagnostic {
+                file: Some("./main.tex".to_string()),
+                line: Some(12),
+                severity: Severity::Warning,
+                message: "hyperref: Token not allowed in a PDF string (Unicode): removing `math shift' on input line 12.".to_string(),
+            },
+            Diagnostic {
+                file: Some("./main.tex".to_string()),
+                line: Some(20),
+                severity: Severity::Warning,
+                message: "Reference `fig:one' on page 1 undefined on input line 20.".to_string(),
+            },
+        ]
+    )
+}
+
+#[test]
+fn file_line_error() {
+    let log = "(./main.tex\n./main.tex:42: Undefined control sequence.\nl.42 \\foo\n\n)";
+    let diagnostics = parse_latex_log(log);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].file.as_deref(), Some("./main.tex"));
+    assert_eq!(diagnostics[0].line, Some(42));
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "./main.tex:42: Undefined control sequence."
+    );
+}
+
+#[test]
+fn warning_display() {
+    let diagnostic = Diagnostic {
+        file: Some("main.tex".to_string()),
+        line: Some(5),
+        severity: Severity::Badbox,
+        message: "Overfull \\hbox (1.0pt too wide)".to_string(),
+    };
+
+    assert_eq!(
+        diagnostic.to_string(),
+        "main.tex:5: warning: Overfull \\hbox (1.0pt too wide)"
+    );
+}
+
+#[test]
+fn no_problems() {
+    assert!(parse_latex_log("(./main.tex [1] (./main.aux) )").is_empty());
+}
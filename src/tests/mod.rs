@@ -1,4 +1,22 @@
 #[cfg(test)]
+mod cache_index;
+#[cfg(test)]
 mod conflict;
 #[cfg(test)]
 mod diff;
+#[cfg(test)]
+mod formats;
+#[cfg(test)]
+mod latex_log;
+#[cfg(test)]
+mod object_store;
+#[cfg(test)]
+mod profile;
+#[cfg(test)]
+mod props;
+#[cfg(test)]
+mod to_strings;
+#[cfg(test)]
+mod within_map;
+#[cfg(test)]
+mod zip_dir;
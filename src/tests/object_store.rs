@@ -0,0 +1,87 @@
+use std::fs;
+
+use chrono::Utc;
+
+use crate::structs::{CacheEntry, CacheIndex, ObjectStore, StoreObject};
+
+#[test]
+fn key_collisions() {
+    let key = ObjectStore::key("https://example.com", 1, "dir/file", 10, 5);
+    assert_eq!(
+        key,
+        ObjectStore::key("https://example.com", 1, "/dir/file/", 10, 5)
+    );
+
+    for other in [
+        ObjectStore::key("https://example.org", 1, "dir/file", 10, 5),
+        ObjectStore::key("https://example.com", 2, "dir/file", 10, 5),
+        ObjectStore::key("https://example.com", 1, "dir/file2", 10, 5),
+        ObjectStore::key("https://example.com", 1, "dir/file", 11, 5),
+        ObjectStore::key("https://example.com", 1, "dir/file", 10, 6),
+    ] {
+        assert_ne!(key, other);
+    }
+}
+
+#[tokio::test]
+async fn save_then_restore() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("file");
+    fs::write(&file, b"content").unwrap();
+
+    let object = StoreObject {
+        path: dir.path().join("objects/ab/cdef"),
+        last_modified: 10,
+        size: 7,
+    };
+    ObjectStore::save(&file, &object).await.unwrap();
+    assert_eq!(fs::read(&object.path).unwrap(), b"content");
+    assert_eq!(
+        fs::read_dir(dir.path().join("objects/ab")).unwrap().count(),
+        1
+    );
+
+    // edits to the restored file stay out of the store
+    let restored = dir.path().join("restored");
+    assert!(ObjectStore::restore(&object, &restored).await);
+    fs::write(&restored, b"edited").unwrap();
+    assert_eq!(fs::read(&object.path).unwrap(), b"content");
+}
+
+#[tokio::test]
+async fn restore_missing() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let object = StoreObject {
+        path: dir.path().join("objects/ab/cdef"),
+        last_modified: 10,
+        size: 7,
+    };
+
+    let restored = dir.path().join("restored");
+    assert!(!ObjectStore::restore(&object, &restored).await);
+    assert!(!restored.exists());
+}
+
+#[test]
+fn expire_only_objects() {
+    let mut index = CacheIndex::default();
+    let now = Utc::now().timestamp() as u64;
+    for (path, accessed) in [
+        (ObjectStore::dir().join("tests/expired"), 0),
+        (ObjectStore::dir().join("tests/used"), now),
+        (CacheIndex::dir().join("fs/tests/stale"), 0),
+    ] {
+        let mut entry = CacheEntry::new(0, 10);
+        entry.accessed = accessed;
+        index.insert(&path, entry);
+    }
+
+    assert_eq!(ObjectStore::expire(&mut index, 3600), (1, 10));
+    assert!(index
+        .get(&ObjectStore::dir().join("tests/expired"))
+        .is_none());
+    assert!(index.get(&ObjectStore::dir().join("tests/used")).is_some());
+    assert!(index
+        .get(&CacheIndex::dir().join("fs/tests/stale"))
+        .is_some());
+}
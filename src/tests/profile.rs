@@ -0,0 +1,80 @@
+use goodmorning_bindings::structs::{BirthDay, CakeDay, ProfileCustomisable, ProfileDetail};
+
+use crate::commands::core::ProfileSet;
+
+fn set(detail: &[&str]) -> ProfileSet {
+    ProfileSet {
+        description: None,
+        detail: detail.iter().map(|detail| detail.to_string()).collect(),
+        birthday: None,
+        cakeday: None,
+        clear: false,
+    }
+}
+
+fn profile() -> ProfileCustomisable {
+    ProfileCustomisable {
+        description: "old".to_string(),
+        details: vec![
+            ProfileDetail::Location("There".to_string()),
+            ProfileDetail::Website("https://example.com".to_string()),
+        ],
+    }
+}
+
+#[test]
+fn text_details() {
+    assert_eq!(
+        set(&["location = Here", "Github=alice"]).details(),
+        [
+            ProfileDetail::Location("Here".to_string()),
+            ProfileDetail::Github("alice".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn dates() {
+    let mut set = set(&[]);
+    set.birthday = Some("2000-02-29".to_string());
+    set.cakeday = Some("12-25".to_string());
+    assert_eq!(
+        set.details(),
+        [
+            ProfileDetail::BirthDay(BirthDay {
+                day: 29,
+                month: 2,
+                year: 2000
+            }),
+            ProfileDetail::CakeDay(CakeDay { day: 25, month: 12 }),
+        ]
+    );
+}
+
+#[test]
+fn replace_same_type() {
+    let mut profile = profile();
+    set(&["location=Here"]).apply(&mut profile);
+    assert_eq!(profile.description, "old");
+    assert_eq!(
+        profile.details,
+        [
+            ProfileDetail::Website("https://example.com".to_string()),
+            ProfileDetail::Location("Here".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn clear_details() {
+    let mut profile = profile();
+    let mut set = set(&["location=Here"]);
+    set.description = Some("new".to_string());
+    set.clear = true;
+    set.apply(&mut profile);
+    assert_eq!(profile.description, "new");
+    assert_eq!(
+        profile.details,
+        [ProfileDetail::Location("Here".to_string())]
+    );
+}
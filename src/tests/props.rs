@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    functions::DEFAULT_VIS,
+    structs::{DiffConflictAction, TreeDiff},
+};
+use goodmorning_bindings::services::v1::{V1DirTreeItem, V1DirTreeNode};
+use proptest::prelude::*;
+
+// generated as a plain tree and turned into a `V1DirTreeNode` once drawn
+#[derive(Debug, Clone)]
+enum Node {
+    File { last_modified: u64, size: u64 },
+    Dir(BTreeMap<String, Node>),
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    // few names and small metadata so separate trees overlap often
+    let file = (0..3u64, 0..3u64).prop_map(|(last_modified, size)| Node::File {
+        last_modified,
+        size,
+    });
+    file.prop_recursive(3, 24, 4, |inner| {
+        prop::collection::btree_map("[a-c]", inner, 0..4).prop_map(Node::Dir)
+    })
+}
+
+fn root() -> impl Strategy<Value = Node> {
+    prop::collection::btree_map("[a-c]", node(), 0..4).prop_map(Node::Dir)
+}
+
+fn build(name: String, node: Node) -> V1DirTreeNode {
+    V1DirTreeNode {
+        name,
+        visibility: DEFAULT_VIS,
+        content: match node {
+            Node::File {
+                last_modified,
+                size,
+            } => V1DirTreeItem::File {
+                last_modified,
+                size,
+            },
+            Node::Dir(content) => V1DirTreeItem::Dir {
+                content: content
+                    .into_iter()
+                    .map(|(name, node)| build(name, node))
+                    .collect(),
+            },
+        },
+    }
+}
+
+fn tree(node: Node) -> V1DirTreeNode {
+    build(String::new(), node)
+}
+
+// every path in the tree mapped to whether it is a directory
+fn shape(node: &V1DirTreeNode) -> BTreeMap<String, bool> {
+    fn recurse(node: &V1DirTreeNode, current: &Path, out: &mut BTreeMap<String, bool>) {
+        if let V1DirTreeItem::Dir { content } = &node.content {
+            for item in content.iter() {
+                let path = current.join(&item.name);
+                out.insert(
+                    path.to_string_lossy().to_string(),
+                    matches!(item.content, V1DirTreeItem::Dir { .. }),
+                );
+                recurse(item, &path, out);
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    recurse(node, &PathBuf::new(), &mut out);
+    out
+}
+
+fn related(this: &str, other: &str) -> bool {
+    Path::new(this).starts_with(other) || Path::new(other).starts_with(this)
+}
+
+fn is_sorted(diff: &TreeDiff) -> bool {
+    [
+        &diff.created,
+        &diff.created_dirs,
+        &diff.changed,
+        &diff.deleted,
+        &diff.type_changed,
+    ]
+    .iter()
+    .all(|items| items.windows(2).all(|pair| pair[0] <= pair[1]))
+}
+
+proptest! {
+    #[test]
+    fn cmp_same_is_empty(a in root()) {
+        let a = tree(a);
+        prop_assert!(TreeDiff::cmp(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn cmp_is_sorted(a in root(), b in root()) {
+        prop_assert!(is_sorted(&TreeDiff::cmp(&tree(a), &tree(b))));
+    }
+
+    #[test]
+    fn apply_gives_new_tree(a in root(), b in root()) {
+        let (mut a, b) = (tree(a), tree(b));
+        TreeDiff::cmp(&a, &b).apply(&mut a);
+        prop_assert_eq!(shape(&a), shape(&b));
+    }
+
+    #[test]
+    fn path_modified_matches_linear_scan(a in root(), b in root()) {
+        let (a, b) = (tree(a), tree(b));
+        let diff = TreeDiff::cmp(&a, &b);
+        // only the sorted lists, everything else in `path_modified` is already a linear scan
+        let diff = TreeDiff {
+            created: diff.created,
+            created_dirs: diff.created_dirs,
+            changed: diff.changed,
+            ..Default::default()
+        };
+
+        for path in shape(&a).into_keys().chain(shape(&b).into_keys()) {
+            let expected = if diff.created.iter().any(|item| item.path == path) {
+                Some(DiffConflictAction::Create)
+            } else if diff.created_dirs.iter().any(|item| item.path == path) {
+                Some(DiffConflictAction::CreateDir)
+            } else if diff.changed.iter().any(|item| item.path == path) {
+                Some(DiffConflictAction::Change)
+            } else {
+                None
+            };
+            prop_assert_eq!(diff.path_modified(&path), expected, "path {}", path);
+        }
+    }
+
+    #[test]
+    fn conflicts_are_symmetric(base in root(), local in root(), remote in root()) {
+        let base = tree(base);
+        let local = TreeDiff::cmp(&base, &tree(local));
+        let remote = TreeDiff::cmp(&base, &tree(remote));
+
+        let paths = |this: &TreeDiff, other: &TreeDiff| {
+            this.conflict(other)
+                .conflicts
+                .into_iter()
+                .map(|item| item.path)
+                .collect::<Vec<_>>()
+        };
+        let ours = paths(&local, &remote);
+        let theirs = paths(&remote, &local);
+
+        // each side reports the path it changed, so a deleted directory on one side is matched
+        // by the file changed inside it on the other
+        for path in ours.iter() {
+            prop_assert!(
+                theirs.iter().any(|other| related(path, other)),
+                "{} has no counterpart in {:?}",
+                path,
+                theirs
+            );
+        }
+        for path in theirs.iter() {
+            prop_assert!(
+                ours.iter().any(|other| related(path, other)),
+                "{} has no counterpart in {:?}",
+                path,
+                ours
+            );
+        }
+    }
+}
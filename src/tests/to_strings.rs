@@ -0,0 +1,28 @@
+use goodmorning_bindings::structs::{BlueRenderDisplay, EmailVerificationDisplay};
+
+use crate::functions::{task_to_string, trigger_to_string};
+
+#[test]
+fn email_verification_trigger() {
+    let display = EmailVerificationDisplay {
+        username: "alice".to_string(),
+        email: "alice@example.com".to_string(),
+    };
+    assert_eq!(
+        trigger_to_string(Box::new(display)),
+        "Verifies the email address `alice@example.com` of user `alice`."
+    );
+}
+
+#[test]
+fn render_task() {
+    let display = BlueRenderDisplay {
+        from: "/blue/world.zip".to_string(),
+        to: "/blue/world".to_string(),
+        preset: "default".to_string(),
+    };
+    assert_eq!(
+        task_to_string(Box::new(display)),
+        "Rendering `/blue/world.zip` with preset `default`.\n      -> `/blue/world`"
+    );
+}
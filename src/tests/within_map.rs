@@ -0,0 +1,58 @@
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+
+use crate::functions::{map_redirect_in, map_redirect_url, with_map_action};
+
+#[test]
+fn redirect_relative_to_request() {
+    assert_eq!(
+        map_redirect_url(
+            "https://example.com/api/storage/v1/diritems/token/maps/world/tiles",
+            "/api/blue/v1/diritems/token/maps/world/tiles"
+        ),
+        "https://example.com/api/blue/v1/diritems/token/maps/world/tiles"
+    );
+}
+
+#[test]
+fn redirect_full_url() {
+    assert_eq!(
+        map_redirect_url(
+            "https://example.com/api/storage/v1/exists",
+            "https://maps.example.com/world/"
+        ),
+        "https://maps.example.com/world/"
+    );
+}
+
+#[test]
+fn map_action_replaced() {
+    assert_eq!(
+        with_map_action(
+            "https://example.com/api/blue/v1/file/token/maps/world/index.html",
+            "exists"
+        ),
+        "https://example.com/api/blue/v1/exists/token/maps/world/index.html"
+    );
+    assert_eq!(
+        with_map_action(
+            "https://example.com/api/blue/v1/usercontent/file/id/1/maps/world/index.html",
+            "exists"
+        ),
+        "https://example.com/api/blue/v1/usercontent/exists/id/1/maps/world/index.html"
+    );
+}
+
+#[test]
+fn map_redirect_by_content_type() {
+    let body = br#"{"type":"WithinMap","redirect":"/api/blue/v1/file/token/maps/world"}"#;
+    let mut headers = HeaderMap::new();
+    assert_eq!(map_redirect_in(&headers, body), None);
+
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    assert_eq!(
+        map_redirect_in(&headers, body).as_deref(),
+        Some("/api/blue/v1/file/token/maps/world")
+    );
+    // a json file that happens to be in storage
+    assert_eq!(map_redirect_in(&headers, br#"{"type":"Other"}"#), None);
+}
@@ -0,0 +1,68 @@
+use std::{
+    fs,
+    io::{Cursor, Read},
+};
+
+use http_body_util::BodyExt;
+use zip::ZipArchive;
+
+use crate::functions::{zip_dir, zip_dir_body};
+
+#[test]
+fn zip_relative_paths() {
+    let dir = tempfile::TempDir::new().unwrap();
+    fs::write(dir.path().join("level.dat"), b"level").unwrap();
+    fs::write(dir.path().join("session.lock"), b"lock").unwrap();
+    fs::create_dir_all(dir.path().join("region/empty")).unwrap();
+    fs::write(dir.path().join("region/r.0.0.mca"), b"region").unwrap();
+
+    let archive = zip_dir(dir.path(), Cursor::new(Vec::new()), |name| {
+        name == "session.lock"
+    })
+    .unwrap();
+    let mut archive = ZipArchive::new(archive).unwrap();
+
+    let mut names = archive.file_names().map(str::to_string).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        ["level.dat", "region/", "region/empty/", "region/r.0.0.mca"]
+    );
+
+    let mut content = String::new();
+    archive
+        .by_name("region/r.0.0.mca")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "region");
+}
+
+#[tokio::test]
+async fn zip_body_reads_back() {
+    let dir = tempfile::TempDir::new().unwrap();
+    fs::write(dir.path().join("level.dat"), b"level").unwrap();
+    fs::write(dir.path().join("session.lock"), b"lock").unwrap();
+    fs::create_dir_all(dir.path().join("region")).unwrap();
+    // larger than a chunk, so the archive is sent in more than one piece
+    let region = (0..200_000u32)
+        .flat_map(u32::to_le_bytes)
+        .collect::<Vec<_>>();
+    fs::write(dir.path().join("region/r.0.0.mca"), &region).unwrap();
+
+    let body = zip_dir_body(dir.path().to_path_buf(), |name| name == "session.lock");
+    let bytes = body.collect().await.unwrap().to_bytes();
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+
+    let mut names = archive.file_names().map(str::to_string).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["level.dat", "region/", "region/r.0.0.mca"]);
+
+    let mut content = Vec::new();
+    archive
+        .by_name("region/r.0.0.mca")
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    assert_eq!(content, region);
+}
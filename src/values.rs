@@ -16,6 +16,8 @@ use crate::structs::{CredsConfig, GmIgnoreDefault, MainConfig};
 
 pub static HTTP: OnceLock<bool> = OnceLock::new();
 pub static YES: OnceLock<bool> = OnceLock::new();
+pub static WAIT: OnceLock<bool> = OnceLock::new();
+pub static NO_INPUT: OnceLock<bool> = OnceLock::new();
 pub static mut CREDS: OnceLock<CredsConfig> = OnceLock::new();
 pub static mut INSTANCE: OnceLock<String> = OnceLock::new();
 pub static mut USER_ID: OnceLock<i64> = OnceLock::new();
@@ -23,8 +25,16 @@ pub static BASE_PATH: OnceLock<String> = OnceLock::new();
 pub static FULL_PATH: OnceLock<bool> = OnceLock::new();
 pub static OUTPUT_DIR: OnceLock<PathBuf> = OnceLock::new();
 pub static DOWNLOAD_RETRIES: OnceLock<u16> = OnceLock::new();
+pub static CONNECT_TIMEOUT: OnceLock<u64> = OnceLock::new();
+pub static READ_TIMEOUT: OnceLock<u64> = OnceLock::new();
+pub static MAX_WAIT: OnceLock<u64> = OnceLock::new();
+pub static HTTPS_PROXY: OnceLock<Option<String>> = OnceLock::new();
+pub static CA_CERTS: OnceLock<Option<PathBuf>> = OnceLock::new();
+pub static TRUSTED_CERT_ONLY: OnceLock<Option<PathBuf>> = OnceLock::new();
 pub static MAX_AGE: OnceLock<u64> = OnceLock::new();
+pub static MAX_CACHE_SIZE: OnceLock<u64> = OnceLock::new();
 pub static AUTO_CLEAN: OnceLock<bool> = OnceLock::new();
+pub static OBJECT_CACHE: OnceLock<bool> = OnceLock::new();
 pub static GMIGNORE_DEFAULT: OnceLock<String> = OnceLock::new();
 pub static mut FULLPATH: OnceLock<bool> = OnceLock::new();
 pub const EXPECT: &str =
@@ -40,24 +50,32 @@ pub mod exit_codes {
 
     use goodmorning_bindings::services::v1::V1Response;
 
+    use crate::structs::CacheIndex;
+
+    /// Saves the changes this command made to the cache index, then exits with `code`.
+    fn exit(code: i32) -> ! {
+        CacheIndex::flush();
+        process::exit(code)
+    }
+
     // 300s: operation not allowed
     //
     /// This operation is only allowed when not logged in.
     pub fn loggedin_not_allowed() {
         error!("3000 This operation is not allowed when logged in.");
-        process::exit(3000)
+        exit(3000)
     }
 
     /// This operation is only allowed when logged in
     pub fn loggedin_only() {
         error!("3001 This operation can only be done when logged in.");
-        process::exit(3001)
+        exit(3001)
     }
 
     /// you don't have the permission to do this action
     pub fn permission_denied() {
         error!("3002 You don't have the permission to do this action.");
-        process::exit(3002)
+        exit(3002)
     }
 
     // 400s: not found
@@ -65,25 +83,25 @@ pub mod exit_codes {
     /// When an optional argument is missing, but is required.
     pub fn missing_argument(msg: &str) {
         error!("4000 Argument `{msg}` is required but not provided.");
-        process::exit(4000)
+        exit(4000)
     }
 
     /// gmrepo.json is missing
     pub fn missing_repo_json() {
         error!("4001 Cannot find gmrepo.json, is this a cloned repo?");
-        process::exit(4001)
+        exit(4001)
     }
 
     /// file not found
     pub fn file_not_found(path: &Path) {
         error!("4002 File not found at {}", path.to_string_lossy());
-        process::exit(4002)
+        exit(4002)
     }
 
     /// directory not found
     pub fn repo_not_found(path: &Path) {
         error!("4003 Repo not found at {}", path.to_string_lossy());
-        process::exit(4003)
+        exit(4003)
     }
 
     // 500s: error/aborted
@@ -91,7 +109,7 @@ pub mod exit_codes {
     /// When "do as I say" failed.
     pub fn doas_failed() {
         error!("5000 Aborted: user did not enter confirm message.");
-        process::exit(5000)
+        exit(5000)
     }
 
     /// .ignore file adding failed
@@ -100,19 +118,19 @@ pub mod exit_codes {
             "5001 Aborted: could not add .ignore file at `{}`.",
             path.to_string_lossy().to_string()
         );
-        process::exit(5001)
+        exit(5001)
     }
 
     /// clone url bad first lined json
     pub fn bad_head_json() {
         error!("5002 Aborted: invalid page first lined JSON in url.");
-        process::exit(5002)
+        exit(5002)
     }
 
     /// bad url format
     pub fn bad_url(msg: &str, url: &str) {
         error!("5003 Invalid url format in {url}: {msg}");
-        process::exit(5003)
+        exit(5003)
     }
 
     /// output path already exists
@@ -121,20 +139,20 @@ pub mod exit_codes {
             "5004 Output path `{}` is already occupied.",
             path.to_string_lossy()
         );
-        process::exit(5004)
+        exit(5004)
     }
 
     /// donwload failed
     pub fn download_failed(path: &str, e: &str) {
         error!("5005 Downloading failed for {path}, aborting.");
         error!("Error content:\n{e}");
-        process::exit(5005)
+        exit(5005)
     }
 
     /// push or pull fail
     pub fn sync_failed(e: Box<dyn Error>) {
         error!("5006 Syncing failed with error {e}, aborting.");
-        process::exit(5006)
+        exit(5006)
     }
 
     /// failed to create .gmignore
@@ -143,31 +161,67 @@ pub mod exit_codes {
             "5007 Failed to create .gmignore in path {} with error {e}.",
             path.to_string_lossy()
         );
-        process::exit(5007)
+        exit(5007)
     }
 
     /// there is a conflict between remote and local
     pub fn repo_conflict() {
         error!("5008 Aborted action as there is a conflict between local and remote.");
-        process::exit(5008)
+        exit(5008)
     }
 
     /// the recieved response does not match expected
     pub fn unexpected_response(expect: &str, got: V1Response) {
         error!("5009 Response rematch, expects {expect}, got {got:?}.");
-        process::exit(5009)
+        exit(5009)
     }
 
     /// an invalid compile format is provided
     pub fn unknown_format(format: &str) {
         error!("5010 Unknown format, got {format}.");
-        process::exit(5010)
+        exit(5010)
     }
 
     /// and invalid compiler is provided
     pub fn unknown_compiler(compiler: &str) {
         error!("5011 Unknown compiler, got {compiler}.");
-        process::exit(5011)
+        exit(5011)
+    }
+
+    /// http client cannot be built from main config
+    pub fn bad_client_config(e: &str) {
+        error!("5012 Failed to build HTTP client from main config: {e}");
+        exit(5012)
+    }
+
+    /// an argument has a value that cannot be used
+    pub fn invalid_argument(arg: &str, msg: &str) {
+        error!("5013 Invalid value for argument `{arg}`: {msg}");
+        exit(5013)
+    }
+
+    /// the formats and compiler of a compile request do not go together
+    pub fn unsupported_compile(msg: &str) {
+        error!("5014 Unsupported compile request: {msg}.");
+        exit(5014)
+    }
+
+    /// gmbuild.toml cannot be used
+    pub fn bad_build_manifest(msg: &str) {
+        error!("5015 Invalid gmbuild.toml: {msg}.");
+        exit(5015)
+    }
+
+    /// some build targets did not compile
+    pub fn build_failed(failed: usize) {
+        error!("5016 {failed} build target(s) failed to compile.");
+        exit(5016)
+    }
+
+    /// the compile task finished with errors
+    pub fn compile_failed() {
+        error!("5017 Compile task failed.");
+        exit(5017)
     }
 
     pub struct FsAction {
@@ -199,6 +253,7 @@ pub mod exit_codes {
         CreateDirectory,
         WriteFile,
         DeleteItem,
+        MoveItem,
     }
 
     impl Display for FsActionType {
@@ -208,6 +263,7 @@ pub mod exit_codes {
                 Self::CreateDirectory => "creating directory",
                 Self::WriteFile => "writing file",
                 Self::DeleteItem => "deleting filesystem item",
+                Self::MoveItem => "moving filesystem item",
             })
         }
     }
@@ -222,8 +278,16 @@ pub fn load() -> Result<(), Box<dyn Error>> {
     let main = MainConfig::load()?;
     trace!("Main config loaded and parsed.");
     DOWNLOAD_RETRIES.set(main.download_retries).unwrap();
+    CONNECT_TIMEOUT.set(main.connect_timeout).unwrap();
+    READ_TIMEOUT.set(main.read_timeout).unwrap();
+    MAX_WAIT.set(main.max_wait).unwrap();
+    HTTPS_PROXY.set(main.https_proxy).unwrap();
+    CA_CERTS.set(main.ca_certs).unwrap();
+    TRUSTED_CERT_ONLY.set(main.trusted_cert_only).unwrap();
     MAX_AGE.set(main.max_age).unwrap();
+    MAX_CACHE_SIZE.set(main.max_cache_size).unwrap();
     AUTO_CLEAN.set(main.auto_clean).unwrap();
+    OBJECT_CACHE.set(main.object_cache).unwrap();
 
     debug!("Loading creds config from {:?}", CredsConfig::path());
     let creds = CredsConfig::load()?;
@@ -0,0 +1,208 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn register_login_logout() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    assert_eq!(id, 1);
+
+    let output = harness.brewer(&["logout"]);
+    assert!(stdout(&output).contains("login details have been removed"));
+
+    let output = harness.brewer(&[
+        "login",
+        "-u",
+        "alice",
+        "-i",
+        &harness.server.url,
+        "-p",
+        PASSWORD,
+    ]);
+    assert!(stdout(&output).contains("You are now logged in"));
+}
+
+#[test]
+fn login_errors() {
+    let harness = Harness::new();
+    harness.register("alice");
+    harness.brewer(&["logout"]);
+
+    let output = harness.brewer(&[
+        "login",
+        "-u",
+        "alice",
+        "-i",
+        &harness.server.url,
+        "-p",
+        "wrong",
+    ]);
+    assert!(stdout(&output).contains("not the correct password"));
+
+    let output = harness.brewer(&[
+        "login",
+        "-u",
+        "nobody",
+        "-i",
+        &harness.server.url,
+        "-p",
+        PASSWORD,
+    ]);
+    assert!(stdout(&output).contains("has not been registered"));
+}
+
+#[test]
+fn register_taken() {
+    let harness = Harness::new();
+    harness.register("alice");
+    harness.brewer(&["logout"]);
+
+    let output = harness.brewer(&[
+        "register",
+        "-u",
+        "Alice",
+        "-e",
+        "other@example.com",
+        "-i",
+        &harness.server.url,
+        "-p",
+        PASSWORD,
+    ]);
+    assert!(stdout(&output).contains("already been taken"));
+}
+
+#[test]
+fn loggedin_only() {
+    let harness = Harness::new();
+
+    let output = harness.brewer(&["logout"]);
+    assert_eq!(output.status.code(), exit_code(3001));
+}
+
+#[test]
+fn no_input() {
+    let harness = Harness::new();
+
+    let output = harness.brewer(&["login", "--no-input", "-i", &harness.server.url]);
+    assert_eq!(output.status.code(), exit_code(4000));
+    assert!(stderr(&output).contains("`identifier`"));
+
+    let output = harness.brewer(&["login", "--no-input", "-u", "alice"]);
+    assert_eq!(output.status.code(), exit_code(4000));
+    assert!(stderr(&output).contains("`instance`"));
+}
+
+#[test]
+fn token_stdin_empty() {
+    let harness = Harness::new();
+
+    let output = harness.brewer(&["login", "--token-stdin", "-i", &harness.server.url]);
+    assert_eq!(output.status.code(), exit_code(4000));
+    assert!(stderr(&output).contains("`token`"));
+}
+
+#[test]
+fn token_login() {
+    let harness = Harness::new();
+    harness.register("alice");
+    harness.brewer(&["logout"]);
+    harness.register("bob");
+    harness.brewer(&["logout"]);
+
+    let output = harness.brewer_stdin(
+        &["login", "--token-stdin", "-i", &harness.server.url],
+        "token-1\n",
+    );
+    assert!(stdout(&output).contains("You are now logged in"));
+    assert_eq!(harness.creds_id(), 1);
+
+    let output = harness.brewer(&["whoami"]);
+    assert!(stdout(&output).contains("alice"));
+}
+
+#[test]
+fn token_login_invalid() {
+    let harness = Harness::new();
+
+    let output = harness.brewer_stdin(
+        &["login", "--token-stdin", "-i", &harness.server.url],
+        "wrong",
+    );
+    assert!(stdout(&output).contains("token you provided is invalid"));
+    assert_eq!(harness.creds_id(), 0);
+}
+
+#[test]
+fn whoami() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+
+    let output = harness.brewer(&["whoami"]);
+    let out = stdout(&output);
+    assert!(out.contains("alice"));
+    assert!(out.contains(&format!("[{id}]")));
+    assert!(out.contains("Email: not verified"));
+    assert!(out.contains("Services: tex"));
+    assert!(out.contains(&format!("Instance: {}", harness.server.url)));
+}
+
+#[test]
+fn profile_set() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+
+    let output = harness.brewer(&[
+        "profile",
+        "set",
+        "-d",
+        "hello",
+        "--detail",
+        "location=Earth",
+        "--birthday",
+        "2000-01-02",
+    ]);
+    assert!(stdout(&output).contains("Profile details updated"));
+    let profile = harness.server.profile(id);
+    assert_eq!(profile.description, "hello");
+    assert_eq!(profile.details.len(), 2);
+
+    harness.brewer(&["profile", "set", "--detail", "location=Mars"]);
+    let profile = harness.server.profile(id);
+    assert_eq!(profile.description, "hello");
+    assert_eq!(profile.details.len(), 2);
+    assert!(serde_json::to_string(&profile.details)
+        .unwrap()
+        .contains("Mars"));
+
+    let output = harness.brewer(&["profile", "set", "--cakeday", "01-02"]);
+    assert!(stdout(&output).contains("only have one of the birthday or cakeday"));
+}
+
+#[test]
+fn profile_set_unknown_detail() {
+    let harness = Harness::new();
+    harness.register("alice");
+
+    let output = harness.brewer(&["profile", "set", "--detail", "shoe=42"]);
+    assert_eq!(output.status.code(), exit_code(5013));
+}
+
+#[test]
+fn trigger_peek_and_run() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    let trigger = harness.server.verification_trigger(id);
+    let link = format!("{}/api/triggers/v1/use/{trigger}", harness.server.url);
+
+    let output = harness.brewer(&["trigger", "peek", &link]);
+    assert!(stdout(&output).contains("email address `alice@example.com` of user `alice`"));
+    assert!(!harness.server.verified(id));
+
+    let output = harness.brewer(&["trigger", "run", &trigger]);
+    assert!(stdout(&output).contains("Trigger event has been ran"));
+    assert!(harness.server.verified(id));
+
+    let output = harness.brewer(&["trigger", "peek", &trigger]);
+    assert!(stdout(&output).contains("does not exist"));
+}
@@ -0,0 +1,121 @@
+#![allow(dead_code)]
+
+mod server;
+pub use server::*;
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+use tempfile::TempDir;
+
+pub const PASSWORD: &str = "correct horse battery staple";
+
+/// A mock server, and a home directory for brewer to keep its config and cache in.
+pub struct Harness {
+    pub server: MockServer,
+    pub home: TempDir,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let home = TempDir::new().unwrap();
+        fs::create_dir_all(home.path().join("work")).unwrap();
+        fs::create_dir_all(home.path().join("config")).unwrap();
+        Self {
+            server: MockServer::start(),
+            home,
+        }
+    }
+
+    /// Directory commands are run in.
+    pub fn work(&self) -> PathBuf {
+        self.home.path().join("work")
+    }
+
+    pub fn brewer(&self, args: &[&str]) -> Output {
+        self.brewer_in(&self.work(), args)
+    }
+
+    pub fn brewer_in(&self, dir: &Path, args: &[&str]) -> Output {
+        self.run(dir, args, "")
+    }
+
+    /// Runs brewer with `stdin` as its input.
+    pub fn brewer_stdin(&self, args: &[&str], stdin: &str) -> Output {
+        self.run(&self.work(), args, stdin)
+    }
+
+    fn run(&self, dir: &Path, args: &[&str], stdin: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_brewer"))
+            .args(args)
+            .current_dir(dir)
+            .env("HOME", self.home.path())
+            .env("XDG_CONFIG_HOME", self.home.path().join("config"))
+            .env("XDG_CACHE_HOME", self.home.path().join("cache"))
+            .env_remove("BREWER_TOKEN")
+            .env_remove("HTTPS_PROXY")
+            .env_remove("https_proxy")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        println!("$ brewer {}", args.join(" "));
+        println!("{}", String::from_utf8_lossy(&output.stdout));
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        output
+    }
+
+    /// Id stored in the creds file, 0 if logged out.
+    pub fn creds_id(&self) -> i64 {
+        let creds = fs::read_to_string(self.home.path().join("config/brewer/creds.yml")).unwrap();
+        creds
+            .lines()
+            .find_map(|line| line.strip_prefix("id: "))
+            .unwrap_or("0")
+            .parse()
+            .unwrap()
+    }
+
+    /// Registers a user and stays logged in as them, returns the user id.
+    pub fn register(&self, username: &str) -> i64 {
+        let output = self.brewer(&[
+            "register",
+            "-u",
+            username,
+            "-e",
+            &format!("{username}@example.com"),
+            "-i",
+            &self.server.url,
+            "-p",
+            PASSWORD,
+        ]);
+        assert!(stdout(&output).contains("you are now logged in"));
+        self.creds_id()
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// Exit status of a brewer exit code, as the OS truncates it to a byte.
+pub fn exit_code(code: i32) -> Option<i32> {
+    Some(code & 0xff)
+}
@@ -0,0 +1,1282 @@
+use std::{
+    collections::BTreeMap,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::to_bytes,
+    extract::{FromRequest, Multipart, Request, State},
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use goodmorning_bindings::{
+    services::v1::{
+        Compiler, ItemVisibility, V1All3, V1Compile, V1DirItem, V1DirTreeItem, V1DirTreeNode,
+        V1EditPublish, V1Error, V1IdentifierType, V1Job, V1MulpiplePaths, V1PasswordId, V1PathOnly,
+        V1Publish, V1Render, V1Response, V1SelfFromTo, V1SetProfile, V1TexUserPublish, V1TokenOnly,
+        V1Unpublish, V1Unqueue, V1UpdatePublish, V1Visibility,
+    },
+    structs::{
+        BlueRenderDisplay, EmailVerificationDisplay, GMServices, ProfileAccount,
+        ProfileCustomisable, ProfileDetail, TexCompileDisplay,
+    },
+    traits::SerdeAny,
+};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+
+const VISIBILITY: V1Visibility = V1Visibility {
+    inherited: true,
+    visibility: ItemVisibility::Public,
+};
+
+/// Published items listed on one page.
+pub const PAGE_SIZE: usize = 2;
+
+pub const PRESETS: [&str; 2] = ["default", "detailed"];
+
+/// An in memory GM instance, serving just enough of the API for brewer.
+pub struct MockServer {
+    pub url: String,
+    state: Arc<Mutex<Instance>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    File {
+        content: Vec<u8>,
+        last_modified: u64,
+    },
+    Dir(BTreeMap<String, Node>),
+}
+
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub email: String,
+    pub password: String,
+    pub token: String,
+    pub root: Node,
+    pub verified: bool,
+    pub created: u64,
+    pub services: Vec<GMServices>,
+    pub profile: ProfileCustomisable,
+    pub publishes: Vec<Published>,
+    /// Directories holding rendered maps, items in them are served by the map service.
+    pub maps: Vec<String>,
+}
+
+pub struct Published {
+    pub item: V1TexUserPublish,
+    /// Path of the published file, under `tex/`.
+    pub path: String,
+}
+
+/// Effect of a job, applied once it is done.
+type Finish = Box<dyn FnOnce(&mut Instance) + Send>;
+
+/// A job of a user.
+struct Job {
+    id: u64,
+    user: i64,
+    task: Box<dyn SerdeAny>,
+    /// When the job started running, `None` while it is queued.
+    started: Option<Instant>,
+}
+
+pub struct Instance {
+    pub users: Vec<User>,
+    /// Last modified of the next write, so every write is distinguishable.
+    clock: u64,
+    jobs: Vec<Job>,
+    next_job: u64,
+    /// How long a job takes once it has started.
+    job_time: Duration,
+    /// Most jobs there can be at once before new ones are turned away.
+    queue_limit: Option<usize>,
+    /// Email verification triggers, by trigger id.
+    triggers: BTreeMap<String, i64>,
+}
+
+type Shared = Arc<Mutex<Instance>>;
+
+impl MockServer {
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(Instance {
+            users: Vec::new(),
+            clock: 1_700_000_000,
+            jobs: Vec::new(),
+            next_job: 1,
+            job_time: Duration::ZERO,
+            queue_limit: None,
+            triggers: BTreeMap::new(),
+        }));
+        let app = Router::new().fallback(handle).with_state(state.clone());
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async move {
+                    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    tx.send(listener.local_addr().unwrap()).unwrap();
+                    axum::serve(listener, app).await.unwrap();
+                })
+        });
+
+        Self {
+            url: format!("http://{}", rx.recv().unwrap()),
+            state,
+        }
+    }
+
+    /// Url of the web page brewer clones from.
+    pub fn page(&self, id: i64, path: &str) -> String {
+        format!("{}/fs/{id}/{}", self.url, path.trim_matches('/'))
+    }
+
+    /// Writes a file as `user`, creating parent directories.
+    pub fn write(&self, user: i64, path: &str, content: &[u8]) {
+        let mut state = self.state.lock().unwrap();
+        let last_modified = state.tick();
+        let root = &mut state.user_mut(user).root;
+
+        let parts = components(path);
+        let (name, parents) = parts.split_last().unwrap();
+        let mut dir = root;
+        for part in parents {
+            let Node::Dir(content) = dir else {
+                panic!("`{part}` is under a file")
+            };
+            dir = content
+                .entry(part.to_string())
+                .or_insert_with(|| Node::Dir(BTreeMap::new()));
+        }
+        let Node::Dir(dir) = dir else {
+            panic!("parent of `{path}` is a file")
+        };
+        dir.insert(
+            name.to_string(),
+            Node::File {
+                content: content.to_vec(),
+                last_modified,
+            },
+        );
+    }
+
+    pub fn read(&self, user: i64, path: &str) -> Option<Vec<u8>> {
+        match self.state.lock().unwrap().user_mut(user).root.get(path) {
+            Some(Node::File { content, .. }) => Some(content.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn exists(&self, user: i64, path: &str) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .user_mut(user)
+            .root
+            .get(path)
+            .is_some()
+    }
+
+    /// Names in the directory at `path`, in order.
+    pub fn list(&self, user: i64, path: &str) -> Vec<String> {
+        match self.state.lock().unwrap().user_mut(user).root.get(path) {
+            Some(Node::Dir(content)) => content.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn remove(&self, user: i64, path: &str) {
+        let mut state = self.state.lock().unwrap();
+        let (parent, name) = state.user_mut(user).root.parent_mut(path).unwrap();
+        parent.remove(name).unwrap();
+    }
+
+    /// Serves the directory at `path` as a rendered map.
+    pub fn map(&self, user: i64, path: &str) {
+        let mut state = self.state.lock().unwrap();
+        let user = state.user_mut(user);
+        assert!(matches!(user.root.get(path), Some(Node::Dir(_))));
+        user.maps.push(path.trim_matches('/').to_string());
+    }
+
+    /// Publishes the file at `tex/<path>`, returns the id of the published item.
+    pub fn publish(&self, user: i64, path: &str, title: &str, desc: &str) -> i64 {
+        let mut state = self.state.lock().unwrap();
+        let published = state.tick();
+        state
+            .user_mut(user)
+            .publish(path, title.to_string(), desc.to_string(), published)
+    }
+
+    pub fn publishes(&self, user: i64) -> Vec<V1TexUserPublish> {
+        let mut state = self.state.lock().unwrap();
+        let user = state.user_mut(user);
+        user.publishes.iter().map(|p| p.item.clone()).collect()
+    }
+
+    pub fn profile(&self, user: i64) -> ProfileCustomisable {
+        self.state.lock().unwrap().user_mut(user).profile.clone()
+    }
+
+    pub fn verified(&self, user: i64) -> bool {
+        self.state.lock().unwrap().user_mut(user).verified
+    }
+
+    /// Creates an email verification trigger for `user`, returns the trigger id.
+    pub fn verification_trigger(&self, user: i64) -> String {
+        let mut state = self.state.lock().unwrap();
+        let id = format!("trigger-{}", state.triggers.len() + 1);
+        state.triggers.insert(id.clone(), user);
+        id
+    }
+
+    /// Ids of jobs that are running or queued.
+    pub fn jobs(&self) -> Vec<u64> {
+        let state = self.state.lock().unwrap();
+        state.jobs.iter().map(|job| job.id).collect()
+    }
+
+    /// Makes every job take `time` to run once it has started.
+    pub fn set_job_time(&self, time: Duration) {
+        self.state.lock().unwrap().job_time = time;
+    }
+
+    /// Makes new jobs fail with `QueueFull` while there are `limit` jobs already.
+    pub fn set_queue_limit(&self, limit: usize) {
+        self.state.lock().unwrap().queue_limit = Some(limit);
+    }
+}
+
+impl User {
+    fn publish(&mut self, path: &str, title: String, desc: String, published: u64) -> i64 {
+        let id = self
+            .publishes
+            .iter()
+            .map(|p| p.item.id)
+            .max()
+            .unwrap_or_default()
+            + 1;
+        let ext = path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext)
+            .unwrap_or_default();
+        self.publishes.push(Published {
+            item: V1TexUserPublish {
+                id,
+                published,
+                title,
+                desc,
+                ext: ext.to_string(),
+            },
+            path: path.trim_matches('/').to_string(),
+        });
+        id
+    }
+
+    fn published(&mut self, id: i64) -> Result<&mut Published, V1Error> {
+        self.publishes
+            .iter_mut()
+            .find(|p| p.item.id == id)
+            .ok_or(V1Error::EntryNotFound)
+    }
+
+    /// The map `path` is in, if any.
+    fn map_of(&self, path: &str) -> Option<&str> {
+        let path = path.trim_matches('/');
+        self.maps
+            .iter()
+            .find(|map| path == map.as_str() || path.starts_with(&format!("{map}/")))
+            .map(String::as_str)
+    }
+
+    fn account(&self) -> ProfileAccount {
+        ProfileAccount {
+            id: self.id,
+            username: self.username.clone(),
+            verified: self.verified,
+            created: self.created,
+            status: String::new(),
+            services: self.services.clone(),
+        }
+    }
+}
+
+impl Instance {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn user_mut(&mut self, id: i64) -> &mut User {
+        self.users.iter_mut().find(|user| user.id == id).unwrap()
+    }
+
+    fn by_token(&mut self, token: &str) -> Result<&mut User, V1Error> {
+        self.users
+            .iter_mut()
+            .find(|user| user.token == token)
+            .ok_or(V1Error::InvalidToken)
+    }
+
+    fn by_id(&mut self, id: &str) -> Result<&mut User, V1Error> {
+        self.users
+            .iter_mut()
+            .find(|user| user.id.to_string() == id)
+            .ok_or(V1Error::NoSuchUser)
+    }
+
+    fn by_name(&mut self, name: &str) -> Result<&mut User, V1Error> {
+        self.users
+            .iter_mut()
+            .find(|user| user.username.eq_ignore_ascii_case(name))
+            .ok_or(V1Error::NoSuchUser)
+    }
+
+    fn new_job(&mut self) -> Result<u64, V1Error> {
+        if self
+            .queue_limit
+            .is_some_and(|limit| self.jobs.len() >= limit)
+        {
+            return Err(V1Error::QueueFull);
+        }
+
+        self.next_job += 1;
+        Ok(self.next_job - 1)
+    }
+}
+
+fn components(path: &str) -> Vec<&str> {
+    path.split('/').filter(|part| !part.is_empty()).collect()
+}
+
+impl Node {
+    fn get(&self, path: &str) -> Option<&Node> {
+        components(path)
+            .into_iter()
+            .try_fold(self, |node, name| match node {
+                Node::Dir(content) => content.get(name),
+                Node::File { .. } => None,
+            })
+    }
+
+    fn dir_mut(&mut self, path: &str) -> Option<&mut BTreeMap<String, Node>> {
+        let node = components(path)
+            .into_iter()
+            .try_fold(self, |node, name| match node {
+                Node::Dir(content) => content.get_mut(name),
+                Node::File { .. } => None,
+            })?;
+        match node {
+            Node::Dir(content) => Some(content),
+            Node::File { .. } => None,
+        }
+    }
+
+    /// Parent directory of the item at `path`, and the name of the item.
+    fn parent_mut<'a>(&mut self, path: &'a str) -> Option<(&mut BTreeMap<String, Node>, &'a str)> {
+        let path = path.trim_matches('/');
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        if name.is_empty() {
+            return None;
+        }
+        Some((self.dir_mut(parent)?, name))
+    }
+
+    fn tree(&self, name: &str) -> V1DirTreeNode {
+        V1DirTreeNode {
+            visibility: VISIBILITY,
+            name: name.to_string(),
+            content: match self {
+                Node::File {
+                    content,
+                    last_modified,
+                } => V1DirTreeItem::File {
+                    last_modified: *last_modified,
+                    size: content.len() as u64,
+                },
+                Node::Dir(content) => V1DirTreeItem::Dir {
+                    content: content.iter().map(|(name, node)| node.tree(name)).collect(),
+                },
+            },
+        }
+    }
+}
+
+fn decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match u8::from_str_radix(path.get(i + 1..i + 3).unwrap_or_default(), 16) {
+            Ok(byte) if bytes[i] == b'%' => {
+                out.push(byte);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap()
+}
+
+/// Splits `<token or id>/<path>`.
+fn split_first(rest: &str) -> (&str, &str) {
+    rest.split_once('/').unwrap_or((rest, ""))
+}
+
+fn json(res: Result<V1Response, V1Error>) -> Response {
+    match res {
+        Ok(res) => (StatusCode::OK, axum::Json(res)).into_response(),
+        Err(kind) => (
+            StatusCode::BAD_REQUEST,
+            axum::Json(V1Response::Error { kind }),
+        )
+            .into_response(),
+    }
+}
+
+/// Responds with an ETag, or `304` if the client already has this body.
+fn cacheable(if_none_match: Option<&str>, content_type: &str, body: Vec<u8>) -> Response {
+    let etag = format!("\"{:x}\"", Sha256::digest(&body));
+    if if_none_match == Some(etag.as_str()) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    (
+        StatusCode::OK,
+        [
+            (header::ETAG, etag),
+            (header::CONTENT_TYPE, content_type.to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn body<T: DeserializeOwned>(req: Request) -> Result<T, V1Error> {
+    let bytes = to_bytes(req.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).map_err(|e| V1Error::External {
+        content: e.to_string(),
+    })
+}
+
+async fn handle(State(state): State<Shared>, req: Request) -> Response {
+    let method = req.method().clone();
+    let path = decode(req.uri().path());
+    let query = req.uri().query().unwrap_or_default().to_string();
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let if_none_match = if_none_match.as_deref();
+
+    if method == Method::GET {
+        if let Some(rest) = path.strip_prefix("/fs/") {
+            let (id, path) = split_first(rest);
+            let head =
+                serde_json::json!({ "path": format!("/{path}"), "id": id.parse::<i64>().unwrap() });
+            return (
+                [(header::CONTENT_TYPE, "text/html")],
+                format!("<!-- {head} -->\n<html></html>"),
+            )
+                .into_response();
+        }
+
+        if let Some(rest) = path.strip_prefix("/api/accounts/v1/") {
+            return json(profile(&state, rest));
+        }
+        if let Some(rest) = path.strip_prefix("/api/triggers/v1/") {
+            return json(trigger(&state, rest));
+        }
+        if path == "/api/blue/v1/presets" {
+            return json(Ok(V1Response::BluePresets {
+                default: PRESETS[0].to_string(),
+                all: PRESETS.map(str::to_string).to_vec(),
+            }));
+        }
+        if let Some(rest) = path.strip_prefix("/api/publish/v1/published-file/id/") {
+            let (user, id) = split_first(rest);
+            let mut state = state.lock().unwrap();
+            let file = state.by_id(user).and_then(|user| {
+                let path = format!("tex/{}", user.published(id.parse().unwrap())?.path);
+                match user.root.get(&path) {
+                    Some(Node::File { content, .. }) => Ok(content.clone()),
+                    _ => Err(V1Error::FileNotFound),
+                }
+            });
+            return match file {
+                Ok(content) => cacheable(if_none_match, "application/octet-stream", content),
+                Err(e) => json(Err(e)),
+            };
+        }
+        if let Some(rest) = path.strip_prefix("/api/publish/v1/") {
+            return json(publishes(&state, rest, &query));
+        }
+
+        // the map service serves the same actions as storage, for items within maps
+        for (prefix, owned, map) in [
+            ("/api/storage/v1/", true, false),
+            ("/api/usercontent/v1/", false, false),
+            ("/api/blue/v1/usercontent/", false, true),
+            ("/api/blue/v1/", true, true),
+        ] {
+            let Some(rest) = path.strip_prefix(prefix) else {
+                continue;
+            };
+            let (action, rest) = split_first(rest);
+            let rest = if owned {
+                rest
+            } else {
+                rest.strip_prefix("id/").unwrap_or(rest)
+            };
+            let (user, path) = split_first(rest);
+
+            let mut state = state.lock().unwrap();
+            let user = match if owned {
+                state.by_token(user)
+            } else {
+                state.by_id(user)
+            } {
+                Ok(user) => user,
+                Err(e) => return json(Err(e)),
+            };
+
+            if !map && user.map_of(path).is_some() {
+                return json(Ok(V1Response::WithinMap {
+                    redirect: map_redirect(action, owned, user, path),
+                }));
+            }
+            if map && user.map_of(path).is_none() {
+                return json(Err(V1Error::FileNotFound));
+            }
+
+            let node = user.root.get(path);
+
+            return match (action, node) {
+                ("file", Some(Node::File { content, .. })) => {
+                    cacheable(if_none_match, "application/octet-stream", content.clone())
+                }
+                ("tree", Some(node)) => {
+                    let name = components(path).last().copied().unwrap_or_default();
+                    let res = V1Response::Tree {
+                        content: node.tree(name),
+                    };
+                    cacheable(
+                        if_none_match,
+                        "application/json",
+                        serde_json::to_vec(&res).unwrap(),
+                    )
+                }
+                ("diritems", Some(Node::Dir(content))) => json(Ok(V1Response::DirContent {
+                    content: content
+                        .iter()
+                        .map(|(name, node)| V1DirItem {
+                            visibility: VISIBILITY,
+                            is_file: matches!(node, Node::File { .. }),
+                            name: name.clone(),
+                            last_modified: match node {
+                                Node::File { last_modified, .. } => *last_modified,
+                                Node::Dir(_) => 0,
+                            },
+                            size: match node {
+                                Node::File { content, .. } => content.len() as u64,
+                                Node::Dir(_) => 0,
+                            },
+                        })
+                        .collect(),
+                })),
+                ("exists", node) => json(Ok(V1Response::Exists {
+                    value: node.is_some(),
+                })),
+                (_, Some(_)) => json(Err(V1Error::TypeMismatch)),
+                (_, None) => (
+                    StatusCode::NOT_FOUND,
+                    axum::Json(V1Response::Error {
+                        kind: V1Error::FileNotFound,
+                    }),
+                )
+                    .into_response(),
+            };
+        }
+    }
+
+    if method == Method::POST {
+        for (prefix, overwrite) in [
+            ("/api/storage/v1/upload-overwrite/", true),
+            ("/api/storage/v1/upload/", false),
+        ] {
+            let Some(rest) = path.strip_prefix(prefix) else {
+                continue;
+            };
+            let (token, path) = split_first(rest);
+            let path = path.to_string();
+            let token = token.to_string();
+
+            let mut multipart = Multipart::from_request(req, &()).await.unwrap();
+            let mut content = Vec::new();
+            while let Some(field) = multipart.next_field().await.unwrap() {
+                if field.name() == Some("file") {
+                    content = field.bytes().await.unwrap().to_vec();
+                }
+            }
+
+            return json(upload(&state, &token, &path, content, overwrite));
+        }
+
+        let res = match path.as_str() {
+            "/api/accounts/v1/create" => match body(req).await {
+                Ok(body) => create(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/accounts/v1/login" => match body(req).await {
+                Ok(body) => login(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/accounts/v1/profile" => match body(req).await {
+                Ok(body) => own_profile(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/accounts/v1/set-profile" => match body(req).await {
+                Ok(body) => set_profile(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/storage/v1/mkdir-multiple" => match body(req).await {
+                Ok(body) => mkdir_multiple(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/storage/v1/delete-multiple" => match body(req).await {
+                Ok(body) => delete_multiple(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/storage/v1/delete" => match body(req).await {
+                Ok(body) => delete(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/storage/v1/move" => match body(req).await {
+                Ok(body) => r#move(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/storage/v1/exists" => match body(req).await {
+                Ok(body) => exists(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/jobs/v1/jobs" => match body(req).await {
+                Ok(body) => jobs(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/jobs/v1/unqueue" => match body(req).await {
+                Ok(body) => unqueue(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/compile/v1/simple" => match body(req).await {
+                Ok(body) => match compile(&state, body) {
+                    Ok(job) => run_job(&state, job).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            "/api/blue/v1/render" => match body(req).await {
+                Ok(body) => match render(&state, body) {
+                    Ok(job) => run_job(&state, job).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            "/api/publish/v1/publish" => match body(req).await {
+                Ok(body) => publish(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/publish/v1/update-publish" => match body(req).await {
+                Ok(body) => update_publish(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/publish/v1/edit-publish" => match body(req).await {
+                Ok(body) => edit_publish(&state, body),
+                Err(e) => Err(e),
+            },
+            "/api/publish/v1/unpublish" => match body(req).await {
+                Ok(body) => unpublish(&state, body),
+                Err(e) => Err(e),
+            },
+            _ => return StatusCode::NOT_FOUND.into_response(),
+        };
+        return json(res);
+    }
+
+    StatusCode::NOT_FOUND.into_response()
+}
+
+/// Where the map service serves the item at `path`, for the same action.
+fn map_redirect(action: &str, owned: bool, user: &User, path: &str) -> String {
+    let path = path.trim_matches('/');
+    if owned {
+        format!("/api/blue/v1/{action}/{}/{path}", user.token)
+    } else {
+        format!("/api/blue/v1/usercontent/{action}/id/{}/{path}", user.id)
+    }
+}
+
+/// Profiles, by `profile/id/<id>`, `profile/name/<name>` or `profile-only/id/<id>`.
+fn profile(state: &Shared, rest: &str) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let (kind, rest) = split_first(rest);
+    let (by, user) = split_first(rest);
+    let user = match by {
+        "id" => state.by_id(user)?,
+        "name" => state.by_name(user)?,
+        _ => return Err(V1Error::NoSuchUser),
+    };
+
+    match kind {
+        "profile" => Ok(V1Response::Profile {
+            profile: user.profile.clone(),
+            account: user.account(),
+        }),
+        "profile-only" => Ok(V1Response::ProfileOnly {
+            profile: user.profile.clone(),
+        }),
+        _ => Err(V1Error::NoSuchUser),
+    }
+}
+
+fn own_profile(state: &Shared, body: V1TokenOnly) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+    Ok(V1Response::Profile {
+        profile: user.profile.clone(),
+        account: user.account(),
+    })
+}
+
+fn set_profile(state: &Shared, body: V1SetProfile) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+
+    let birthday = body
+        .profile
+        .details
+        .iter()
+        .any(|detail| matches!(detail, ProfileDetail::BirthDay(_)));
+    let cakeday = body
+        .profile
+        .details
+        .iter()
+        .any(|detail| matches!(detail, ProfileDetail::CakeDay(_)));
+    if birthday && cakeday {
+        return Err(V1Error::BirthCakeConflict);
+    }
+
+    user.profile = body.profile;
+    Ok(V1Response::ProfileUpdated)
+}
+
+/// Triggers, by `<action>/<id>`.
+fn trigger(state: &Shared, rest: &str) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let (action, id) = split_first(rest);
+    let user = *state.triggers.get(id).ok_or(V1Error::TriggerNotFound)?;
+
+    match action {
+        "peek" => {
+            let user = state.user_mut(user);
+            Ok(V1Response::TriggerPeek {
+                value: Box::new(EmailVerificationDisplay {
+                    username: user.username.clone(),
+                    email: user.email.clone(),
+                }),
+            })
+        }
+        "use" => {
+            state.triggers.remove(id);
+            state.user_mut(user).verified = true;
+            Ok(V1Response::Triggered)
+        }
+        "revoke" => {
+            state.triggers.remove(id);
+            Ok(V1Response::Revoked)
+        }
+        _ => Err(V1Error::TriggerNotFound),
+    }
+}
+
+/// Published items, by `publish/id/<user>/<id>` or `publishes/id/<user>?page=<page>`.
+fn publishes(state: &Shared, rest: &str, query: &str) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let (kind, rest) = split_first(rest);
+    let rest = rest.strip_prefix("id/").unwrap_or(rest);
+    let (user, id) = split_first(rest);
+    let user = state.by_id(user)?;
+
+    match kind {
+        "publish" => Ok(V1Response::TexUserPublish {
+            value: user
+                .published(id.parse().map_err(|_| V1Error::EntryNotFound)?)?
+                .item
+                .clone(),
+        }),
+        "publishes" => {
+            let page = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("page="))
+                .map_or(1, |page| page.parse::<usize>().unwrap())
+                .max(1);
+            // newest first
+            let items = user
+                .publishes
+                .iter()
+                .rev()
+                .skip((page - 1) * PAGE_SIZE)
+                .take(PAGE_SIZE)
+                .map(|p| p.item.clone())
+                .collect();
+            let continuation = if user.publishes.len() > page * PAGE_SIZE {
+                Some(page as u64 + 1)
+            } else {
+                None
+            };
+            Ok(V1Response::TexUserPublishes {
+                items,
+                continuation,
+            })
+        }
+        _ => Err(V1Error::EntryNotFound),
+    }
+}
+
+fn publish(state: &Shared, body: V1Publish) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let published = state.tick();
+    let user = state.by_token(&body.token)?;
+    if !matches!(
+        user.root
+            .get(&format!("tex/{}", body.path.trim_matches('/'))),
+        Some(Node::File { .. })
+    ) {
+        return Err(V1Error::FileNotFound);
+    }
+
+    let id = user.publish(&body.path, body.title, body.desc, published);
+    Ok(V1Response::TexPublished { id })
+}
+
+fn update_publish(state: &Shared, body: V1UpdatePublish) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let published = state.tick();
+    let user = state.by_token(&body.token)?;
+    let entry = user.published(body.id)?;
+    entry.path = body.path.trim_matches('/').to_string();
+    entry.item.published = published;
+    Ok(V1Response::TexPublishUpdated)
+}
+
+fn edit_publish(state: &Shared, body: V1EditPublish) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+    let entry = user.published(body.id)?;
+    entry.item.title = body.title;
+    entry.item.desc = body.desc;
+    Ok(V1Response::TexPublishUpdated)
+}
+
+fn unpublish(state: &Shared, body: V1Unpublish) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+    user.published(body.id)?;
+    user.publishes.retain(|p| p.item.id != body.id);
+    Ok(V1Response::FileItemDeleted)
+}
+
+/// A job to queue or wait on, along with the response once it is done.
+struct NewJob {
+    job: Job,
+    res: V1Response,
+    finish: Finish,
+}
+
+/// Queues the job and responds once it is done. Jobs run one at a time, and like on a real
+/// server a job carries on when the request that created it goes away.
+async fn run_job(state: &Shared, new: NewJob) -> Result<V1Response, V1Error> {
+    let NewJob { job, res, finish } = new;
+    let id = job.id;
+    state.lock().unwrap().jobs.push(job);
+
+    let state = state.clone();
+    let worker = tokio::spawn(async move {
+        loop {
+            {
+                let mut state = state.lock().unwrap();
+                let idle = state.jobs.iter().all(|job| job.started.is_none());
+                let next = state.jobs.first().map(|job| job.id);
+                let job_time = state.job_time;
+                // gone from the list if it has been unqueued
+                let job = state
+                    .jobs
+                    .iter_mut()
+                    .find(|job| job.id == id)
+                    .ok_or(V1Error::JobNotFound)?;
+
+                match job.started {
+                    Some(started) if started.elapsed() >= job_time => {
+                        state.jobs.retain(|job| job.id != id);
+                        finish(&mut state);
+                        return Ok(res);
+                    }
+                    None if idle && next == Some(id) => job.started = Some(Instant::now()),
+                    _ => {}
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    worker.await.unwrap()
+}
+
+fn jobs(state: &Shared, body: V1TokenOnly) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?.id;
+
+    let (current, queue) = state
+        .jobs
+        .iter()
+        .filter(|job| job.user == user)
+        .partition::<Vec<_>, _>(|job| job.started.is_some());
+    let listed = |jobs: Vec<&Job>| {
+        jobs.into_iter()
+            .map(|job| V1Job {
+                id: job.id,
+                task: job.task.clone(),
+            })
+            .collect()
+    };
+    Ok(V1Response::Jobs {
+        current: listed(current),
+        queue: listed(queue),
+    })
+}
+
+fn unqueue(state: &Shared, body: V1Unqueue) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?.id;
+
+    let position = state
+        .jobs
+        .iter()
+        .position(|job| job.id == body.id && job.user == user && job.started.is_none())
+        .ok_or(V1Error::JobNotFound)?;
+    state.jobs.remove(position);
+    Ok(V1Response::Unqueued)
+}
+
+fn create(state: &Shared, body: V1All3) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    if state
+        .users
+        .iter()
+        .any(|user| user.username.eq_ignore_ascii_case(&body.username))
+    {
+        return Err(V1Error::UsernameTaken);
+    }
+    if state.users.iter().any(|user| user.email == body.email) {
+        return Err(V1Error::EmailTaken);
+    }
+
+    let id = state.users.len() as i64 + 1;
+    let token = format!("token-{id}");
+    let created = state.tick();
+    state.users.push(User {
+        id,
+        username: body.username,
+        email: body.email,
+        password: body.password,
+        token: token.clone(),
+        root: Node::Dir(BTreeMap::new()),
+        verified: false,
+        created,
+        services: vec![GMServices::Tex],
+        profile: ProfileCustomisable::default(),
+        publishes: Vec::new(),
+        maps: Vec::new(),
+    });
+
+    Ok(V1Response::Created {
+        id,
+        token,
+        verify: false,
+    })
+}
+
+fn login(state: &Shared, body: V1PasswordId) -> Result<V1Response, V1Error> {
+    let state = state.lock().unwrap();
+    let user = state
+        .users
+        .iter()
+        .find(|user| match body.identifier_type {
+            V1IdentifierType::Email => user.email == body.identifier,
+            V1IdentifierType::Username => user.username.eq_ignore_ascii_case(&body.identifier),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        })
+        .ok_or(V1Error::NoSuchUser)?;
+
+    if user.password != body.password {
+        return Err(V1Error::PasswordIncorrect);
+    }
+
+    Ok(V1Response::Login {
+        id: user.id,
+        token: user.token.clone(),
+    })
+}
+
+fn upload(
+    state: &Shared,
+    token: &str,
+    path: &str,
+    content: Vec<u8>,
+    overwrite: bool,
+) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let last_modified = state.tick();
+    let user = state.by_token(token)?;
+    let (parent, name) = user.root.parent_mut(path).ok_or(V1Error::FileNotFound)?;
+
+    match parent.get(name) {
+        Some(Node::Dir(_)) => return Err(V1Error::TypeMismatch),
+        Some(_) if !overwrite => return Err(V1Error::PathOccupied),
+        _ => {}
+    }
+
+    parent.insert(
+        name.to_string(),
+        Node::File {
+            content,
+            last_modified,
+        },
+    );
+    Ok(V1Response::FileItemCreated)
+}
+
+fn mkdir_multiple(state: &Shared, body: V1MulpiplePaths) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+
+    let res = body
+        .paths
+        .iter()
+        .map(|path| match user.root.parent_mut(path) {
+            Some((parent, name)) if !parent.contains_key(name) => {
+                parent.insert(name.to_string(), Node::Dir(BTreeMap::new()));
+                V1Response::FileItemCreated
+            }
+            Some(_) => V1Response::Error {
+                kind: V1Error::PathOccupied,
+            },
+            None => V1Response::Error {
+                kind: V1Error::FileNotFound,
+            },
+        })
+        .collect();
+
+    Ok(V1Response::Multi { res })
+}
+
+fn delete_multiple(state: &Shared, body: V1MulpiplePaths) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+
+    let res = body
+        .paths
+        .iter()
+        .map(|path| {
+            match user
+                .root
+                .parent_mut(path)
+                .and_then(|(parent, name)| parent.remove(name))
+            {
+                Some(_) => V1Response::FileItemDeleted,
+                None => V1Response::Error {
+                    kind: V1Error::FileNotFound,
+                },
+            }
+        })
+        .collect();
+
+    Ok(V1Response::Multi { res })
+}
+
+fn r#move(state: &Shared, body: V1SelfFromTo) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+
+    if user.root.get(&body.to).is_some() {
+        return Err(V1Error::PathOccupied);
+    }
+    if user.root.parent_mut(&body.to).is_none() {
+        return Err(V1Error::FileNotFound);
+    }
+
+    let (parent, name) = user
+        .root
+        .parent_mut(&body.from)
+        .ok_or(V1Error::FileNotFound)?;
+    let node = parent.remove(name).ok_or(V1Error::FileNotFound)?;
+
+    let (parent, name) = user.root.parent_mut(&body.to).unwrap();
+    parent.insert(name.to_string(), node);
+    Ok(V1Response::Moved)
+}
+
+fn delete(state: &Shared, body: V1PathOnly) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+    let (parent, name) = user
+        .root
+        .parent_mut(&body.path)
+        .ok_or(V1Error::FileNotFound)?;
+    parent.remove(name).ok_or(V1Error::FileNotFound)?;
+    Ok(V1Response::FileItemDeleted)
+}
+
+fn exists(state: &Shared, body: V1PathOnly) -> Result<V1Response, V1Error> {
+    let mut state = state.lock().unwrap();
+    let user = state.by_token(&body.token)?;
+    // the redirect is to the item itself, not to an `exists` check of it
+    if user.map_of(&body.path).is_some() {
+        return Ok(V1Response::WithinMap {
+            redirect: map_redirect("file", true, user, &body.path),
+        });
+    }
+    Ok(V1Response::Exists {
+        value: user.root.get(&body.path).is_some(),
+    })
+}
+
+fn compile(state: &Shared, body: V1Compile) -> Result<NewJob, V1Error> {
+    let mut state = state.lock().unwrap();
+    let id = state.new_job()?;
+    let user = state.by_token(&body.token)?;
+
+    let source = format!("tex/{}", body.path.trim_matches('/'));
+    let Some(Node::File { content, .. }) = user.root.get(&source) else {
+        return Err(V1Error::FileNotFound);
+    };
+
+    let ext = match body.to {
+        goodmorning_bindings::services::v1::ToFormat::Html => "html",
+        goodmorning_bindings::services::v1::ToFormat::Pdf => "pdf",
+        #[allow(unreachable_patterns)]
+        _ => return Err(V1Error::InvalidCompileRequest),
+    };
+    let newpath = match body.path.trim_matches('/').rsplit_once('.') {
+        Some((stem, _)) => format!("{stem}.{ext}"),
+        None => format!("{}.{ext}", body.path.trim_matches('/')),
+    };
+    // an undefined control sequence fails the compile like it would in LaTeX
+    if content.windows(10).any(|part| part == b"\\undefined") {
+        let name = body.path.rsplit('/').next().unwrap_or_default();
+        return Err(V1Error::CompileError {
+            content: format!("(./{name}\n! Undefined control sequence.\nl.1 \\undefined\n\n)"),
+        });
+    }
+    let compiled = [b"compiled: ".as_slice(), content].concat();
+    let output = format!("tex/{newpath}");
+    if user.root.parent_mut(&output).is_none() {
+        return Err(V1Error::FileNotFound);
+    }
+
+    let user = user.id;
+    Ok(NewJob {
+        job: Job {
+            id,
+            user,
+            task: Box::new(TexCompileDisplay {
+                path: body.path.clone(),
+                from: body.from,
+                to: body.to,
+                compiler: body.compiler.unwrap_or(Compiler::Default),
+            }),
+            started: None,
+        },
+        res: V1Response::TexCompiled { id, newpath },
+        finish: Box::new(move |state| {
+            let last_modified = state.tick();
+            if let Some((parent, name)) = state.user_mut(user).root.parent_mut(&output) {
+                parent.insert(
+                    name.to_string(),
+                    Node::File {
+                        content: compiled,
+                        last_modified,
+                    },
+                );
+            }
+        }),
+    })
+}
+
+/// Renders the zipped world at `from` into a map at `to`.
+fn render(state: &Shared, body: V1Render) -> Result<NewJob, V1Error> {
+    let mut state = state.lock().unwrap();
+    let id = state.new_job()?;
+    let user = state.by_token(&body.token)?;
+
+    if !matches!(user.root.get(&body.from), Some(Node::File { .. })) {
+        return Err(V1Error::FileNotFound);
+    }
+    if !PRESETS.contains(&body.preset.as_str()) {
+        return Err(V1Error::Any {
+            value: format!("no preset named {}", body.preset).into(),
+        });
+    }
+    let to = body.to.trim_matches('/').to_string();
+    if user.root.get(&to).is_some() {
+        return Err(V1Error::PathOccupied);
+    }
+    if user.root.parent_mut(&to).is_none() {
+        return Err(V1Error::FileNotFound);
+    }
+
+    let user = user.id;
+    Ok(NewJob {
+        job: Job {
+            id,
+            user,
+            task: Box::new(BlueRenderDisplay {
+                from: body.from.clone(),
+                to: to.clone(),
+                preset: body.preset.clone(),
+            }),
+            started: None,
+        },
+        res: V1Response::BlueRendered {
+            id,
+            newpath: to.clone(),
+        },
+        finish: Box::new(move |state| {
+            let last_modified = state.tick();
+            let user = state.user_mut(user);
+            if let Some((parent, name)) = user.root.parent_mut(&to) {
+                let tiles = BTreeMap::from([(
+                    "index.html".to_string(),
+                    Node::File {
+                        content: b"<html></html>".to_vec(),
+                        last_modified,
+                    },
+                )]);
+                parent.insert(name.to_string(), Node::Dir(tiles));
+                user.maps.push(to);
+            }
+        }),
+    })
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use std::fs;
+
+use common::*;
+
+#[test]
+fn upload() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    fs::write(harness.work().join("local.txt"), b"content").unwrap();
+
+    let output = harness.brewer(&["upload", "local.txt", "remote.txt"]);
+    assert!(output.status.success());
+    assert_eq!(harness.server.read(id, "remote.txt").unwrap(), b"content");
+
+    let output = harness.brewer(&["upload", "local.txt", "remote.txt"]);
+    assert!(stdout(&output).contains("already exists"));
+
+    fs::write(harness.work().join("local.txt"), b"changed").unwrap();
+    harness.brewer(&["upload", "-f", "local.txt", "remote.txt"]);
+    assert_eq!(harness.server.read(id, "remote.txt").unwrap(), b"changed");
+}
+
+#[test]
+fn exist() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "here.txt", b"");
+
+    let output = harness.brewer(&["exist", "here.txt"]);
+    assert!(stdout(&output).contains("does exist"));
+
+    let output = harness.brewer(&["exist", "gone.txt"]);
+    assert!(stdout(&output).contains("does not exist"));
+}
+
+#[test]
+fn compile() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/doc.md", b"# Title");
+
+    let output = harness.brewer(&["compile", "doc.md"]);
+    assert!(stdout(&output).contains("/tex/doc.html"));
+    assert_eq!(
+        harness.server.read(id, "tex/doc.html").unwrap(),
+        b"compiled: # Title"
+    );
+
+    let output = harness.brewer(&["compile", "missing.md"]);
+    assert!(stdout(&output).contains("cannot be found"));
+}
+
+#[test]
+fn compile_download() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/doc.md", b"# Title");
+
+    let output = harness.brewer(&["compile", "doc.md", "--download", "doc.html"]);
+    assert!(stdout(&output).contains("downloaded to doc.html"));
+    assert_eq!(
+        std::fs::read(harness.work().join("doc.html")).unwrap(),
+        b"compiled: # Title"
+    );
+}
+
+#[test]
+fn compile_error() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/bad.tex", b"\\undefined");
+
+    let output = harness.brewer(&["compile", "bad.tex"]);
+    assert_eq!(output.status.code(), exit_code(5017));
+    assert!(stdout(&output).contains("Undefined control sequence"));
+
+    let output = harness.brewer(&["compile", "bad.tex", "--json"]);
+    assert_eq!(output.status.code(), exit_code(5017));
+    let diagnostics: serde_json::Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(diagnostics[0]["file"], "/tex/bad.tex");
+    assert_eq!(diagnostics[0]["line"], 1);
+}
+
+#[test]
+fn compile_json() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/doc.md", b"# Title");
+
+    let output = harness.brewer(&["compile", "doc.md", "--json"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "[]");
+
+    let output = harness.brewer(&["compile", "missing.md", "--json"]);
+    assert_eq!(output.status.code(), exit_code(5009));
+    let error: serde_json::Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(error["type"], "FileNotFound");
+}
+
+#[test]
+fn exist_within_map() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness
+        .server
+        .write(id, "maps/world/index.html", b"<html></html>");
+    harness.server.map(id, "maps/world");
+
+    let output = harness.brewer(&["exist", "maps/world/index.html"]);
+    assert!(stdout(&output).contains("does exist"));
+    assert!(stderr(&output).contains("within the map at /api/blue/v1/file/"));
+
+    let output = harness.brewer(&["exist", "maps/world/gone.html"]);
+    assert!(stdout(&output).contains("does not exist"));
+
+    let output = harness.brewer(&["exist", "maps/world/index.html", "--no-follow"]);
+    let out = stdout(&output);
+    assert!(out.contains("within a map at /api/blue/v1/file/"));
+    assert!(!out.contains("exist"));
+}
+
+#[test]
+fn ls_within_map() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "maps/world/tiles/0.png", b"png");
+    harness.server.map(id, "maps/world");
+
+    let output = harness.brewer(&["ls", "maps/world/tiles"]);
+    assert!(stdout(&output).contains("0.png"));
+    assert!(stderr(&output).contains("Showing items within the map"));
+
+    let output = harness.brewer(&["ls", "maps/world/tiles", "--no-follow"]);
+    assert!(stdout(&output).contains("within a map at"));
+    assert!(!stdout(&output).contains("0.png"));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+
+/// The id printed by a detached compile or render.
+fn queued(output: &std::process::Output) -> u64 {
+    let out = stdout(output);
+    let id = out
+        .split("Job [")
+        .nth(1)
+        .and_then(|rest| rest.split(']').next())
+        .unwrap_or_else(|| panic!("no job id in {out:?}"));
+    id.parse().unwrap()
+}
+
+#[test]
+fn compile_detach() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/doc.md", b"# Title");
+    harness.server.set_job_time(Duration::from_secs(2));
+
+    let output = harness.brewer(&["compile", "doc.md", "--detach"]);
+    assert!(output.status.success());
+    let job = queued(&output);
+    assert_eq!(harness.server.jobs(), vec![job]);
+    assert!(!harness.server.exists(id, "tex/doc.html"));
+
+    let output = harness.brewer(&["jobs", "wait", &job.to_string()]);
+    assert!(stdout(&output).contains(&format!("Job [{job}] is no longer running")));
+    assert!(harness.server.jobs().is_empty());
+    assert_eq!(
+        harness.server.read(id, "tex/doc.html").unwrap(),
+        b"compiled: # Title"
+    );
+}
+
+#[test]
+fn compile_detach_error() {
+    let harness = Harness::new();
+    harness.register("alice");
+
+    let output = harness.brewer(&["compile", "missing.md", "--detach"]);
+    assert!(stdout(&output).contains("cannot be found"));
+    assert!(harness.server.jobs().is_empty());
+}
+
+#[test]
+fn compile_queue_position() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/one.md", b"one");
+    harness.server.write(id, "tex/two.md", b"two");
+    harness.server.set_job_time(Duration::from_secs(2));
+
+    let first = queued(&harness.brewer(&["compile", "one.md", "--detach"]));
+    let output = harness.brewer(&["compile", "two.md", "--queue-position"]);
+    let out = stdout(&output);
+    assert!(out.contains(&format!("Job [{}] is queued at position 1.", first + 1)));
+    assert!(out.contains("/tex/two.html"));
+    assert!(harness.server.exists(id, "tex/two.html"));
+
+    let output = harness.brewer(&["compile", "two.md", "--queue-position", "--detach"]);
+    assert_eq!(output.status.code(), exit_code(5013));
+}
+
+#[test]
+fn list_and_cancel() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/one.md", b"one");
+    harness.server.write(id, "tex/two.md", b"two");
+    harness.server.set_job_time(Duration::from_secs(30));
+
+    let first = queued(&harness.brewer(&["compile", "one.md", "--detach"]));
+    let second = queued(&harness.brewer(&["compile", "two.md", "--detach"]));
+
+    let output = harness.brewer(&["jobs"]);
+    let out = stdout(&output);
+    assert!(out.contains(&format!("[{first}]")));
+    assert!(out.contains("Compiling `two.md`"));
+
+    // the first job is running, the second is still queued behind it
+    let output = harness.brewer(&["jobs", "cancel", &second.to_string()]);
+    assert!(stdout(&output).contains("unqueued"));
+    assert_eq!(harness.server.jobs(), vec![first]);
+
+    let output = harness.brewer(&["jobs", "cancel", &first.to_string()]);
+    assert!(stdout(&output).contains("already been done"));
+    assert_eq!(harness.server.jobs(), vec![first]);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::fs;
+
+use common::*;
+
+#[test]
+fn publish_show() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    let item = harness
+        .server
+        .publish(id, "paper.pdf", "Paper", "About things");
+
+    let output = harness.brewer(&["publish", "show", &item.to_string()]);
+    let out = stdout(&output);
+    assert!(out.contains(&format!("[{item}] Paper")));
+    assert!(out.contains("Description: About things"));
+    assert!(out.contains(&format!("/api/publish/v1/published-file/id/{id}/{item}")));
+}
+
+#[test]
+fn publish_rm() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    let item = harness
+        .server
+        .publish(id, "paper.pdf", "Paper", "About things");
+
+    let output = harness.brewer(&["publish", "rm", &item.to_string()]);
+    assert!(output.status.success());
+    assert!(harness.server.publishes(id).is_empty());
+}
+
+#[test]
+fn publish_edit() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    let item = harness
+        .server
+        .publish(id, "paper.pdf", "Paper", "About things");
+
+    let output = harness.brewer(&["publish", "edit", &item.to_string(), "-t", "Final"]);
+    assert!(stdout(&output).contains("has been updated"));
+    let publishes = harness.server.publishes(id);
+    assert_eq!(publishes[0].title, "Final");
+    assert_eq!(publishes[0].desc, "About things");
+
+    let output = harness.brewer(&["publish", "edit", &item.to_string()]);
+    assert_eq!(output.status.code(), exit_code(4000));
+}
+
+#[test]
+fn publishes_all() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    let titles = ["First", "Second", "Third", "Fourth", "Fifth"];
+    for title in titles {
+        harness.server.publish(id, "paper.pdf", title, "");
+    }
+
+    // pages are newest first
+    let output = harness.brewer(&["publishes"]);
+    let out = stdout(&output);
+    assert!(out.contains("Fifth") && out.contains("Fourth"));
+    assert!(!out.contains("Third"));
+
+    let output = harness.brewer(&["publishes", "--all"]);
+    let out = stdout(&output);
+    for title in titles {
+        assert!(out.contains(title), "{title} missing from {out}");
+    }
+}
+
+#[test]
+fn publishes_mirror() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    harness.server.write(id, "tex/notes.md", b"# Notes");
+    let paper = harness.server.publish(id, "paper.pdf", "Paper", "");
+    let notes = harness.server.publish(id, "notes.md", "Notes", "");
+    let mirror = harness.work().join("mirror");
+
+    let get = ["publishes", "get", "--all", "-o", "mirror"];
+    let output = harness.brewer(&get);
+    assert!(stdout(&output).contains("2 downloaded, 0 up to date, 0 removed."));
+    assert_eq!(
+        fs::read(mirror.join(format!("{paper}.pdf"))).unwrap(),
+        b"%PDF"
+    );
+    assert!(mirror.join(".gmpublishes.json").exists());
+
+    let output = harness.brewer(&get);
+    assert!(stdout(&output).contains("0 downloaded, 2 up to date, 0 removed."));
+
+    // unpublished items are only removed from the mirror with --prune
+    harness.brewer(&["publish", "rm", &notes.to_string()]);
+    let output = harness.brewer(&get);
+    assert!(stdout(&output).contains("run with --prune"));
+    assert!(mirror.join(format!("{notes}.md")).exists());
+
+    let output = harness.brewer(&[&get[..], &["--prune"]].concat());
+    assert!(stdout(&output).contains(&format!("Removed [{notes}]")));
+    assert!(!mirror.join(format!("{notes}.md")).exists());
+    assert!(mirror.join(format!("{paper}.pdf")).exists());
+
+    let output = harness.brewer(&["publishes", "get", "--prune", &paper.to_string()]);
+    assert_eq!(output.status.code(), exit_code(5013));
+}
+
+#[test]
+fn publish_get_failed_keeps_copy() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/paper.pdf", b"%PDF");
+    let item = harness.server.publish(id, "paper.pdf", "Paper", "");
+    let path = harness.work().join("paper.pdf");
+    fs::write(&path, b"old").unwrap();
+
+    harness.server.remove(id, "tex/paper.pdf");
+    let output = harness.brewer(&["publishes", "get", &item.to_string(), "-o", "paper.pdf"]);
+    assert_eq!(output.status.code(), exit_code(5005));
+    assert_eq!(fs::read(&path).unwrap(), b"old");
+    // the partial download is not left behind
+    let leftover = fs::read_dir(harness.work())
+        .unwrap()
+        .filter_map(Result::ok)
+        .any(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"));
+    assert!(!leftover);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use std::fs;
+
+use common::*;
+
+/// Registers alice with an empty `maps` directory and a local world directory at `work/world`.
+fn world() -> (Harness, i64) {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "maps/.keep", b"");
+
+    let world = harness.work().join("world");
+    fs::create_dir_all(world.join("region")).unwrap();
+    fs::write(world.join("level.dat"), b"level").unwrap();
+    fs::write(world.join("region/r.0.0.mca"), b"region").unwrap();
+    fs::write(world.join("session.lock"), b"").unwrap();
+    (harness, id)
+}
+
+/// Archives uploaded next to `maps/world`.
+fn uploads(harness: &Harness, id: i64) -> Vec<String> {
+    harness
+        .server
+        .list(id, "maps")
+        .into_iter()
+        .filter(|name| name.starts_with("world.") && name.ends_with(".zip"))
+        .collect()
+}
+
+#[test]
+fn render_local() {
+    let (harness, id) = world();
+
+    let output = harness.brewer(&["render", "world", "maps/world", "--local"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(harness.server.exists(id, "maps/world/index.html"));
+    assert_eq!(uploads(&harness, id).len(), 1);
+}
+
+#[test]
+fn render_local_clean() {
+    let (harness, id) = world();
+
+    let output = harness.brewer(&["render", "world", "maps/world", "--local", "--clean"]);
+    assert!(stdout(&output).contains("Uploaded world has been deleted."));
+    assert!(harness.server.exists(id, "maps/world/index.html"));
+    assert!(uploads(&harness, id).is_empty());
+}
+
+#[test]
+fn render_local_failed() {
+    let (harness, id) = world();
+    harness.server.write(id, "maps/world/old.html", b"old");
+
+    // the target is taken, the uploaded world is removed even without --clean
+    let output = harness.brewer(&["render", "world", "maps/world", "--local"]);
+    assert!(stdout(&output).contains("Uploaded world has been deleted."));
+    assert!(uploads(&harness, id).is_empty());
+    assert_eq!(
+        harness.server.read(id, "maps/world/old.html").unwrap(),
+        b"old"
+    );
+}
+
+#[test]
+fn render_local_not_a_world() {
+    let (harness, id) = world();
+
+    let output = harness.brewer(&["render", "missing", "maps/world", "--local"]);
+    assert_eq!(output.status.code(), exit_code(4002));
+
+    let output = harness.brewer(&["render", "world/level.dat", "maps/world", "--local"]);
+    assert_eq!(output.status.code(), exit_code(5013));
+    assert!(uploads(&harness, id).is_empty());
+}
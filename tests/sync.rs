@@ -0,0 +1,256 @@
+mod common;
+
+use std::{fs, thread, time::Duration};
+
+use common::*;
+
+/// Registers alice with a `project` directory, and clones it into `work/project`.
+fn cloned() -> (Harness, i64) {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "project/a.txt", b"hello");
+    harness.server.write(id, "project/sub/b.txt", b"world");
+
+    let output = harness.brewer(&["clone", &harness.server.page(id, "project")]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("you are now up to date"));
+    // local changes are told apart by their mtime, which is in seconds
+    thread::sleep(Duration::from_secs(1));
+
+    (harness, id)
+}
+
+#[test]
+fn clone() {
+    let (harness, _) = cloned();
+    let project = harness.work().join("project");
+
+    assert_eq!(fs::read(project.join("a.txt")).unwrap(), b"hello");
+    assert_eq!(fs::read(project.join("sub/b.txt")).unwrap(), b"world");
+    assert!(project.join(".gmrepo.json").exists());
+    assert!(project.join(".gmignore").exists());
+}
+
+#[test]
+fn push_pull_round_trip() {
+    let (harness, id) = cloned();
+    let project = harness.work().join("project");
+
+    fs::write(project.join("a.txt"), b"hello again").unwrap();
+    fs::write(project.join("c.txt"), b"new").unwrap();
+    fs::create_dir(project.join("empty")).unwrap();
+    fs::remove_file(project.join("sub/b.txt")).unwrap();
+
+    let output = harness.brewer_in(&project, &["push"]);
+    assert!(stdout(&output).contains("updates are pushed to remote"));
+    assert_eq!(
+        harness.server.read(id, "project/a.txt").unwrap(),
+        b"hello again"
+    );
+    assert_eq!(harness.server.read(id, "project/c.txt").unwrap(), b"new");
+    assert!(harness.server.exists(id, "project/empty"));
+    assert!(!harness.server.exists(id, "project/sub/b.txt"));
+
+    harness.server.write(id, "project/d.txt", b"from remote");
+    harness.server.remove(id, "project/c.txt");
+
+    let output = harness.brewer_in(&project, &["pull"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read(project.join("d.txt")).unwrap(), b"from remote");
+    assert!(!project.join("c.txt").exists());
+    assert_eq!(fs::read(project.join("a.txt")).unwrap(), b"hello again");
+
+    let output = harness.brewer_in(&project, &["push"]);
+    assert!(stdout(&output).contains("Remote is up to date"));
+}
+
+#[test]
+fn pull_up_to_date() {
+    let (harness, _) = cloned();
+    let project = harness.work().join("project");
+
+    // the second pull revalidates the cached tree instead of fetching it again
+    for _ in 0..2 {
+        let output = harness.brewer_in(&project, &["pull"]);
+        assert!(stdout(&output).contains("You are up to date"));
+    }
+}
+
+#[test]
+fn pull_conflict() {
+    let (harness, id) = cloned();
+    let project = harness.work().join("project");
+
+    fs::write(project.join("a.txt"), b"local").unwrap();
+    harness.server.write(id, "project/a.txt", b"remote");
+
+    let output = harness.brewer_in(&project, &["pull"]);
+    assert_eq!(output.status.code(), exit_code(5008));
+    assert!(stdout(&output).contains("a.txt"));
+    assert_eq!(fs::read(project.join("a.txt")).unwrap(), b"local");
+
+    let output = harness.brewer_in(&project, &["pull", "-f"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read(project.join("a.txt")).unwrap(), b"remote");
+}
+
+#[test]
+fn push_conflict() {
+    let (harness, id) = cloned();
+    let project = harness.work().join("project");
+
+    fs::write(project.join("a.txt"), b"local").unwrap();
+    harness.server.write(id, "project/a.txt", b"remote");
+
+    let output = harness.brewer_in(&project, &["push"]);
+    assert_eq!(output.status.code(), exit_code(5008));
+    assert_eq!(harness.server.read(id, "project/a.txt").unwrap(), b"remote");
+}
+
+#[test]
+fn push_move() {
+    let (harness, id) = cloned();
+    let project = harness.work().join("project");
+    // the .gmignore made by clone is new to the remote
+    harness.brewer_in(&project, &["push"]);
+
+    fs::rename(project.join("sub"), project.join("moved")).unwrap();
+    fs::rename(project.join("a.txt"), project.join("renamed.txt")).unwrap();
+
+    let output = harness.brewer_in(&project, &["push"]);
+    assert!(stdout(&output).contains("Moving objects"));
+    assert!(!stdout(&output).contains("Uploading"));
+    assert_eq!(
+        harness.server.read(id, "project/renamed.txt").unwrap(),
+        b"hello"
+    );
+    assert_eq!(
+        harness.server.read(id, "project/moved/b.txt").unwrap(),
+        b"world"
+    );
+    assert!(!harness.server.exists(id, "project/a.txt"));
+    assert!(!harness.server.exists(id, "project/sub"));
+}
+
+#[test]
+fn push_not_owner() {
+    let (harness, id) = cloned();
+    harness.brewer(&["logout"]);
+    harness.register("bob");
+
+    let output = harness.brewer(&["clone", &harness.server.page(id, "project"), "-o", "theirs"]);
+    assert!(output.status.success());
+
+    let theirs = harness.work().join("theirs");
+    fs::write(theirs.join("a.txt"), b"bob was here").unwrap();
+    let output = harness.brewer_in(&theirs, &["push"]);
+    assert_eq!(output.status.code(), exit_code(3002));
+    assert_eq!(harness.server.read(id, "project/a.txt").unwrap(), b"hello");
+}
+
+#[test]
+fn pull_outside_repo() {
+    let harness = Harness::new();
+    harness.register("alice");
+
+    let output = harness.brewer(&["pull"]);
+    assert_eq!(output.status.code(), exit_code(4001));
+}
+
+#[test]
+fn build() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    harness.server.write(id, "tex/docs/paper.md", b"# Paper");
+
+    let output = harness.brewer(&["clone", &harness.server.page(id, "tex/docs")]);
+    assert!(output.status.success());
+
+    let docs = harness.work().join("docs");
+    fs::write(docs.join("slides.md"), b"# Slides").unwrap();
+    fs::write(
+        docs.join("gmbuild.toml"),
+        r#"
+[[target]]
+name = "paper"
+source = "paper.md"
+output = "out/paper.html"
+
+[[target]]
+name = "slides"
+source = "slides.md"
+
+[[target]]
+name = "appendix"
+source = "appendix.md"
+"#,
+    )
+    .unwrap();
+
+    let output = harness.brewer_in(&docs, &["build", "paper"]);
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read(docs.join("out/paper.html")).unwrap(),
+        b"compiled: # Paper"
+    );
+    // pending changes are pushed before compiling
+    assert_eq!(
+        harness.server.read(id, "tex/docs/slides.md").unwrap(),
+        b"# Slides"
+    );
+    assert!(!harness.server.exists(id, "tex/docs/slides.html"));
+
+    let output = harness.brewer_in(&docs, &["build"]);
+    assert_eq!(output.status.code(), exit_code(5016));
+    assert_eq!(
+        harness.server.read(id, "tex/docs/slides.html").unwrap(),
+        b"compiled: # Slides"
+    );
+    let table = stdout(&output);
+    assert!(table.contains("/tex/docs/slides.html"));
+    assert!(table.contains("failed"));
+    // the output downloaded by the first build is not pushed back as a source
+    assert!(!harness.server.exists(id, "tex/docs/out/paper.html"));
+
+    let output = harness.brewer_in(&docs, &["build", "missing"]);
+    assert_eq!(output.status.code(), exit_code(5013));
+
+    fs::write(
+        docs.join("gmbuild.toml"),
+        "[[target]]\nname = \"paper\"\nsource = \"paper.md\"\noutput = \"../paper.html\"\n",
+    )
+    .unwrap();
+    let output = harness.brewer_in(&docs, &["build"]);
+    assert_eq!(output.status.code(), exit_code(5015));
+    assert!(!harness.work().join("paper.html").exists());
+}
+
+#[test]
+fn build_queue_full() {
+    let harness = Harness::new();
+    let id = harness.register("alice");
+    let sources = ["one", "two", "three"];
+    for name in sources {
+        harness
+            .server
+            .write(id, &format!("tex/docs/{name}.md"), name.as_bytes());
+    }
+    harness.server.set_queue_limit(1);
+    harness.server.set_job_time(Duration::from_millis(500));
+
+    let output = harness.brewer(&["clone", &harness.server.page(id, "tex/docs")]);
+    assert!(output.status.success());
+
+    let docs = harness.work().join("docs");
+    let manifest = sources
+        .iter()
+        .map(|name| format!("[[target]]\nname = \"{name}\"\nsource = \"{name}.md\"\n"))
+        .collect::<String>();
+    fs::write(docs.join("gmbuild.toml"), manifest).unwrap();
+
+    let output = harness.brewer_in(&docs, &["build"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    for name in sources {
+        assert!(harness.server.exists(id, &format!("tex/docs/{name}.html")));
+    }
+}